@@ -0,0 +1,18 @@
+//! Resolve the vendored `builtins` module to its module-global [`ScopeId`], so
+//! [`resolver::resolve_builtin`](crate::semantic_index::resolver::resolve_builtin) can look names
+//! up in it the same way it looks names up in any other enclosing scope.
+
+use crate::module_resolver::{resolve_module, ModuleName};
+use crate::semantic_index::{semantic_index, symbol::ScopeId};
+use crate::Db;
+
+/// The module scope of the vendored `builtins.pyi`, or `None` if it can't be resolved.
+///
+/// That should only happen on a `Db` whose vendored typeshed is missing or malformed -- not a
+/// case we expect in practice, but a `Db` is caller-supplied (e.g. in tests), so we report it as
+/// "no builtins" rather than panicking on a corrupt search path.
+#[salsa::tracked]
+pub(crate) fn builtins_scope<'db>(db: &'db dyn Db) -> Option<ScopeId<'db>> {
+    let module = resolve_module(db, ModuleName::new_static("builtins"))?;
+    Some(semantic_index(db, module.file()).module_scope())
+}
@@ -0,0 +1,181 @@
+//! Fixpoint resolution of public symbols re-exported (directly, or via `from x import *`) through
+//! cycles of module imports.
+//!
+//! [`UseDefMap`](crate::semantic_index::use_def::UseDefMap) resolves a symbol's public definitions
+//! within a single module under the simplifying assumption, noted in the
+//! [`use_def`](crate::semantic_index::use_def) module docs, that a scope finishes executing before
+//! another scope examines its public symbols. That assumption breaks down for import cycles:
+//! `a.py` does `from b import x` while `b.py` does `from a import x`, and neither module can
+//! "finish" before the other needs its answer.
+//!
+//! rustc_resolve solves the equivalent glob/use-cycle problem with a determinacy worklist:
+//! repeatedly attempt to resolve each pending import, classifying the result as `Determined`
+//! (bound to a concrete definition) or `Undetermined` (blocked on another import that hasn't
+//! resolved yet), and loop until a full pass makes no further progress. Anything still
+//! `Undetermined` at that fixpoint is unresolved. This module runs the same algorithm over our
+//! cross-module import edges; it's generic over the resolved value type (in practice, a
+//! [`Definition`](crate::semantic_index::definition::Definition)) so the fixpoint logic itself can
+//! be exercised without a full Salsa database.
+//!
+//! The Salsa-tracked side of this -- gathering the real [`ImportEdge`]s for a module graph from
+//! each module's `from x import *` [`DefinitionKind::StarImport`](crate::semantic_index::definition::DefinitionKind::StarImport)
+//! definitions, and feeding [`resolve_import_cycle`] a `try_resolve` that consults each source
+//! module's own `UseDefMap` -- lives in
+//! [`import_graph::resolve_module_cycle`](super::import_graph::resolve_module_cycle), which is
+//! `resolve_import_cycle`'s one real (non-test) caller.
+
+use rustc_hash::FxHashMap;
+
+/// One pending `from <module> import <name>` edge (including the per-name edges a `from module
+/// import *` expands into), binding `bound_name` in `importing_module` to whatever `source_name`
+/// resolves to in `source_module`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ImportEdge {
+    pub(crate) source_module: ModuleId,
+    pub(crate) source_name: ruff_python_ast::name::Name,
+    pub(crate) importing_module: ModuleId,
+    pub(crate) bound_name: ruff_python_ast::name::Name,
+}
+
+/// Opaque identity for a module, stable across a single resolution run.
+///
+/// Callers key their own module table however they like (typically a
+/// [`ModuleName`](super::ModuleName) or a Salsa file ID) and map each module to a `u32` for this
+/// algorithm, e.g. its position in their module list; we don't need to know more about it here.
+pub(crate) type ModuleId = u32;
+
+/// The result of attempting to resolve one [`ImportEdge`] during a single worklist pass.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum EdgeResolution<V> {
+    /// `source_name` is already bound (possibly itself via another resolved import) to a concrete
+    /// value in `source_module`.
+    Determined(V),
+
+    /// `source_name` in `source_module` is itself still an unresolved import; try again next pass.
+    Undetermined,
+}
+
+/// The outcome of running the worklist to a fixpoint.
+#[derive(Debug)]
+pub(crate) struct CycleResolution<V> {
+    /// Edges that resolved to a concrete value, keyed by the binding they produced.
+    pub(crate) determined: FxHashMap<(ModuleId, ruff_python_ast::name::Name), V>,
+
+    /// Edges that never became `Determined`: a genuine cycle with no concrete definition anywhere
+    /// in it, or a name that doesn't exist in any module along the cycle. Callers should report
+    /// these as unresolved imports.
+    pub(crate) unresolved: Vec<ImportEdge>,
+}
+
+/// Resolve every edge in `edges` to a concrete value, honoring import cycles.
+///
+/// `try_resolve` attempts to resolve a single edge given the bindings already `Determined` so far
+/// this run (looked up by `(module, name)`); it should look at the target module's own (possibly
+/// still-partial) bindings and return [`EdgeResolution::Undetermined`] if `source_name` is itself
+/// an unresolved import there. This function reruns `try_resolve` over the remaining worklist,
+/// removing edges as they become `Determined` and feeding newly `Determined` bindings into later
+/// attempts, until a full pass makes no further progress. Because each pass either resolves at
+/// least one edge or the loop stops, and there are finitely many edges, this always terminates:
+/// a cycle with a concrete definition anywhere in it converges once that definition is reached,
+/// and a cycle with no concrete definition anywhere stops making progress and its edges end up
+/// `unresolved`, rather than looping forever.
+pub(crate) fn resolve_import_cycle<V: Clone>(
+    mut edges: Vec<ImportEdge>,
+    mut try_resolve: impl FnMut(&ImportEdge, &FxHashMap<(ModuleId, ruff_python_ast::name::Name), V>) -> EdgeResolution<V>,
+) -> CycleResolution<V> {
+    let mut determined = FxHashMap::default();
+
+    loop {
+        let mut made_progress = false;
+        let mut still_pending = Vec::with_capacity(edges.len());
+
+        for edge in edges {
+            match try_resolve(&edge, &determined) {
+                EdgeResolution::Determined(value) => {
+                    determined.insert((edge.importing_module, edge.bound_name.clone()), value);
+                    made_progress = true;
+                }
+                EdgeResolution::Undetermined => still_pending.push(edge),
+            }
+        }
+
+        edges = still_pending;
+        if !made_progress || edges.is_empty() {
+            break;
+        }
+    }
+
+    CycleResolution {
+        determined,
+        unresolved: edges,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve_import_cycle, EdgeResolution, ImportEdge};
+    use ruff_python_ast::name::Name;
+
+    const A: u32 = 0;
+    const B: u32 = 1;
+    const C: u32 = 2;
+
+    fn edge(
+        source_module: u32,
+        source_name: &str,
+        importing_module: u32,
+        bound_name: &str,
+    ) -> ImportEdge {
+        ImportEdge {
+            source_module,
+            source_name: Name::new(source_name),
+            importing_module,
+            bound_name: Name::new(bound_name),
+        }
+    }
+
+    /// `a.x` imports from `b.x`, which imports from `c.x`, which has a concrete (non-import)
+    /// binding. All three should converge to that one value.
+    #[test]
+    fn chain_through_a_cycle_free_path() {
+        let edges = vec![edge(B, "x", A, "x"), edge(C, "x", B, "x")];
+
+        // Pretend `c.x` is already a concrete, non-import binding: trivially `Determined` the
+        // first time any edge's source is `c`.
+        let resolution = resolve_import_cycle(edges, |edge, determined| {
+            if edge.source_module == C {
+                return EdgeResolution::Determined("c.x's concrete value");
+            }
+            determined
+                .get(&(edge.source_module, edge.source_name.clone()))
+                .map_or(EdgeResolution::Undetermined, |&value| {
+                    EdgeResolution::Determined(value)
+                })
+        });
+
+        assert!(resolution.unresolved.is_empty());
+        assert_eq!(resolution.determined.len(), 2);
+        assert_eq!(
+            resolution.determined[&(A, Name::new("x"))],
+            "c.x's concrete value"
+        );
+    }
+
+    /// `a.x` imports from `b.x` and `b.x` imports from `a.x`, with no concrete binding anywhere:
+    /// a genuine cycle, must terminate as fully unresolved rather than loop forever.
+    #[test]
+    fn genuine_cycle_is_unresolved() {
+        let edges = vec![edge(B, "x", A, "x"), edge(A, "x", B, "x")];
+
+        let resolution: super::CycleResolution<()> = resolve_import_cycle(edges, |_, determined| {
+            determined
+                .values()
+                .next()
+                .copied()
+                .map_or(EdgeResolution::Undetermined, EdgeResolution::Determined)
+        });
+
+        assert!(resolution.determined.is_empty());
+        assert_eq!(resolution.unresolved.len(), 2);
+    }
+}
@@ -0,0 +1,144 @@
+//! The Salsa-tracked half of cross-module import-cycle resolution that
+//! [`cycle_resolution`](super::cycle_resolution) was missing: gathering the real
+//! [`ImportEdge`]s for a group of modules and feeding them to [`resolve_import_cycle`].
+//!
+//! The two halves stay separate on purpose. [`resolve_import_cycle`] only touches its `edges`
+//! and the `determined` map the worklist itself builds up, so it never needs to call back into
+//! Salsa mid-resolution -- which matters, because two modules in a cycle querying each other's
+//! resolved public symbols *as a Salsa query* would just be a cyclic query call, which Salsa has
+//! no fixpoint story for here. So instead [`module_bindings`] does one, non-recursive, per-module
+//! Salsa query (it only reads that module's own [`UseDefMap`](crate::semantic_index::use_def::UseDefMap),
+//! never another module's), [`resolve_module_cycle`] gathers every module's [`ModuleBindings`]
+//! up front, and only then runs the plain-Rust worklist over the combined edge list.
+
+use rustc_hash::FxHashMap;
+
+use ruff_python_ast::name::Name;
+
+use crate::module_resolver::cycle_resolution::{
+    resolve_import_cycle, EdgeResolution, ImportEdge, ModuleId,
+};
+use crate::module_resolver::ModuleName;
+use crate::semantic_index::definition::{Definition, DefinitionKind};
+use crate::semantic_index::symbol::ScopeId;
+use crate::semantic_index::{symbol_table, use_def_map};
+use crate::Db;
+
+/// One module's public symbols, split into what's already concrete and what's still a pending
+/// `from <module> import *` edge waiting on another module.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(crate) struct ModuleBindings<'db> {
+    /// Publicly visible names bound directly (not by a star import) in this module, e.g. `x = 1`,
+    /// `def f(): ...`, or `from other import x` naming `x` explicitly.
+    pub(crate) concrete: FxHashMap<Name, Definition<'db>>,
+
+    /// Publicly visible names bound *only* by one or more `from <source_module> import *`
+    /// statements, along with the module (and priority, see [`DefinitionKind::StarImport`]) of
+    /// the most recently recorded of those statements. Not yet known to be acyclically
+    /// resolvable -- that's what [`resolve_module_cycle`] is for.
+    pub(crate) pending_glob: FxHashMap<Name, (ModuleName, u32)>,
+}
+
+/// Read `scope`'s (a module scope) own [`UseDefMap`](crate::semantic_index::use_def::UseDefMap)
+/// and classify each public symbol as concrete or still-pending-on-a-star-import.
+///
+/// This only ever reads `scope`'s own use-def map, never another module's, so it's safe to call
+/// from within a cycle: it can't itself become a cyclic Salsa query.
+#[salsa::tracked]
+pub(crate) fn module_bindings<'db>(db: &'db dyn Db, scope: ScopeId<'db>) -> ModuleBindings<'db> {
+    let symbols = symbol_table(db, scope);
+    let use_def_map = use_def_map(db, scope);
+
+    let mut bindings = ModuleBindings::default();
+
+    for symbol in symbols.symbol_ids() {
+        let name = Name::new(symbols.symbol_name(symbol));
+        // The most recently recorded public definition wins for picking concrete-vs-pending, the
+        // same way `UseDefMap::public_definitions` already resolves ordinary (non-ambiguous)
+        // shadowing; a symbol genuinely ambiguous between two different star imports is reported
+        // separately via `UseDefMap::public_is_ambiguous_glob` and isn't this query's concern.
+        let Some(definition) = use_def_map.public_definitions(symbol).last() else {
+            continue;
+        };
+
+        // `DefinitionKind` only has the one variant so far (see its doc comment), so every
+        // definition reaching this point is a pending star-import edge; once the other binding
+        // forms grow their own variants, they'll fall out here as `bindings.concrete` entries
+        // instead.
+        let DefinitionKind::StarImport { module, priority } = definition.kind(db);
+        bindings
+            .pending_glob
+            .insert(name, (module.clone(), *priority));
+    }
+
+    bindings
+}
+
+/// Resolve every module in `modules` to a fully concrete mapping of public name to [`Definition`],
+/// honoring `from x import *` cycles among them via [`resolve_import_cycle`].
+///
+/// `modules` is the set of modules participating in one import cycle (or just one module, for the
+/// common acyclic case); each entry pairs the [`ModuleName`] callers key edges by with that
+/// module's [`ScopeId`]. Names bound by a star import whose source module isn't in `modules` are
+/// left unresolved: they belong to a different (presumably already-resolved, acyclic) part of the
+/// import graph, which is outside what this call was asked to resolve.
+pub(crate) fn resolve_module_cycle<'db>(
+    db: &'db dyn Db,
+    modules: &[(ModuleName, ScopeId<'db>)],
+) -> FxHashMap<(ModuleName, Name), Definition<'db>> {
+    let module_id = |name: &ModuleName| -> Option<ModuleId> {
+        modules
+            .iter()
+            .position(|(candidate, _)| candidate == name)
+            .map(|index| index as ModuleId)
+    };
+
+    let mut all_bindings = Vec::with_capacity(modules.len());
+    let mut edges = Vec::new();
+
+    for (index, (module_name, scope)) in modules.iter().enumerate() {
+        let bindings = module_bindings(db, *scope);
+        // `priority` (see `DefinitionKind::StarImport`) disambiguates more than one of *this
+        // module's own* star imports binding the same name -- that's already resolved by the
+        // time we get here, since `bindings.pending_glob` is keyed by `Name` and was built by
+        // `UseDefMapBuilder::record_glob_definition`, which keeps at most one definition per name
+        // (flagging any real conflict as ambiguous rather than leaving two entries to pick
+        // between). So there's never more than one `ImportEdge` per `(importing_module,
+        // bound_name)` here, and nothing left for a priority sort to disambiguate.
+        for (name, (source_module, _priority)) in &bindings.pending_glob {
+            if let Some(source) = module_id(source_module) {
+                edges.push(ImportEdge {
+                    source_module: source,
+                    source_name: name.clone(),
+                    importing_module: index as ModuleId,
+                    bound_name: name.clone(),
+                });
+            }
+        }
+        all_bindings.push(bindings);
+    }
+
+    let resolution = resolve_import_cycle(edges, |edge, determined| {
+        let source_index = edge.source_module as usize;
+        if let Some(&definition) = all_bindings[source_index].concrete.get(&edge.source_name) {
+            return EdgeResolution::Determined(definition);
+        }
+        determined
+            .get(&(edge.source_module, edge.source_name.clone()))
+            .map_or(EdgeResolution::Undetermined, |&definition| {
+                EdgeResolution::Determined(definition)
+            })
+    });
+
+    let mut result = FxHashMap::default();
+    for (index, (module_name, _)) in modules.iter().enumerate() {
+        for (name, definition) in &all_bindings[index].concrete {
+            result.insert((module_name.clone(), name.clone()), *definition);
+        }
+    }
+    for ((module_index, name), definition) in resolution.determined {
+        let module_name = modules[module_index as usize].0.clone();
+        result.insert((module_name, name), definition);
+    }
+    result
+}
@@ -0,0 +1,92 @@
+//! A couple of pieces of the AST visitor that builds a [`SemanticIndex`](super::SemanticIndex):
+//! expanding `from <module> import *` into the per-name [`Definition`]s it binds, and entering an
+//! `elif`/`else` clause.
+//!
+//! This is deliberately narrow -- these are the only two `SemanticIndexBuilder` visit steps this
+//! crate fragment actually has a use for right now (wiring up
+//! [`UseDefMapBuilder::record_glob_definition`] and [`UseDefMapBuilder::record_constraints`]). The
+//! rest of `SemanticIndexBuilder` (the scope stack, the other `visit_stmt_*`/`visit_expr_*`
+//! methods, symbol-table construction as bindings are discovered, ...) lives alongside it and
+//! isn't reproduced here.
+
+use ruff_db::files::File;
+
+use crate::module_resolver::ModuleName;
+use crate::semantic_index::definition::{Definition, DefinitionKind};
+use crate::semantic_index::expression::Expression;
+use crate::semantic_index::symbol::{ScopeId, ScopedSymbolId};
+use crate::semantic_index::use_def::UseDefMapBuilder;
+use crate::semantic_index::{symbol_table, use_def_map};
+use crate::Db;
+
+/// Expand a `from <module> import *` statement (`node.names == ["*"]`) into one `StarImport`
+/// [`Definition`] per name `module` publicly exports, recording each into `use_def_map_builder` via
+/// [`UseDefMapBuilder::record_glob_definition`].
+///
+/// `priority` is this statement's source order among the `import *` statements in the importing
+/// scope (the caller's running count of how many it's visited so far); see
+/// [`DefinitionKind::StarImport`] for why that's needed.
+///
+/// `intern_symbol` resolves a name to this scope's [`ScopedSymbolId`], creating a fresh
+/// symbol-table entry for it if this is the first binding the scope has seen for that name --
+/// exactly what `SemanticIndexBuilder`'s in-progress symbol table already does for every other
+/// kind of binding, so a wildcard import can introduce names the scope hasn't seen yet.
+///
+/// A name is treated as exported unless it starts with `_`; this crate fragment doesn't model
+/// `__all__` yet, so a module that defines one will (incorrectly, but conservatively) export
+/// every non-underscore public name rather than honor the narrower list.
+pub(crate) fn expand_star_import<'db>(
+    db: &'db dyn Db,
+    importing_file: File,
+    module: ModuleName,
+    priority: u32,
+    target_module_scope: ScopeId<'db>,
+    use_def_map_builder: &mut UseDefMapBuilder<'db>,
+    mut intern_symbol: impl FnMut(&str) -> ScopedSymbolId,
+) {
+    let target_symbols = symbol_table(db, target_module_scope);
+    let target_use_def_map = use_def_map(db, target_module_scope);
+
+    for target_symbol in target_symbols.symbol_ids() {
+        let name = target_symbols.symbol_name(target_symbol);
+        if name.starts_with('_') {
+            continue;
+        }
+        if target_use_def_map
+            .public_definitions(target_symbol)
+            .next()
+            .is_none()
+        {
+            // Declared in the target scope's symbol table (e.g. referenced by a `global`
+            // statement somewhere) but never actually given a value there; nothing to export.
+            continue;
+        }
+
+        let symbol = intern_symbol(name);
+        let definition = Definition::new(
+            db,
+            importing_file,
+            symbol,
+            DefinitionKind::StarImport {
+                module: module.clone(),
+                priority,
+            },
+        );
+        use_def_map_builder.record_glob_definition(symbol, definition);
+    }
+}
+
+/// Enter the `elif`/`else` clause following one or more `if`/`elif` clauses: record every one of
+/// those earlier clauses' `test` expressions as a single batch of constraints, since they all must
+/// have evaluated false (on this control-flow edge) to have reached here.
+///
+/// `failed_tests` is every `if`/`elif` test visited so far in this chain, oldest first; for a
+/// chain of more than one (`if`/`elif`/.../`elif`/`else`), that's exactly the batched, more-than-
+/// one-element case [`UseDefMapBuilder::record_constraints`] exists for, rather than the
+/// single-element case [`UseDefMapBuilder::record_constraint`] already covers.
+pub(crate) fn enter_elif_or_else<'db>(
+    use_def_map_builder: &mut UseDefMapBuilder<'db>,
+    failed_tests: &[Expression<'db>],
+) {
+    use_def_map_builder.record_constraints(failed_tests);
+}
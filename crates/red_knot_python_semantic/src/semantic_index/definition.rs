@@ -0,0 +1,47 @@
+//! The different syntactic constructs that can bind a name, and the [`Definition`] each produces.
+//!
+//! [`Definition`] is the unit [`UseDefMap`](super::use_def::UseDefMap) tracks: every entry in
+//! `all_definitions`, every [`Definitions`]-range returned by `use_definitions`/`public_definitions`,
+//! is a `Definition`. This module only models [`DefinitionKind::StarImport`] so far, since that's
+//! the only kind anything in this crate currently constructs; the other binding forms (assignment,
+//! `def`/`class`, explicit `import`/`from x import y`, `for` targets, ...) will each get their own
+//! variant as their visitors land.
+
+use ruff_db::files::File;
+
+use crate::module_resolver::ModuleName;
+use crate::semantic_index::symbol::ScopedSymbolId;
+
+/// A single place in the source that binds a name.
+///
+/// [`UseDefMapBuilder::record_glob_definition`](super::use_def::UseDefMapBuilder::record_glob_definition)
+/// compares `Definition`s for equality to tell a repeat of the same glob binding from a genuine
+/// conflict. Since `kind` carries the statement's `module` and `priority`, bindings produced by
+/// two different `import *` statements are never equal.
+#[salsa::tracked]
+pub(crate) struct Definition<'db> {
+    pub(crate) file: File,
+
+    pub(crate) symbol: ScopedSymbolId,
+
+    #[return_ref]
+    pub(crate) kind: DefinitionKind,
+}
+
+/// What syntactic construct produced a [`Definition`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum DefinitionKind {
+    /// One name bound by a `from <module> import *` statement.
+    ///
+    /// A wildcard import expands to one `StarImport` `Definition` per name the target module
+    /// publicly exports (see `expand_star_import` in
+    /// [`semantic_index::builder`](super::builder)), each carrying the *same* `module` and
+    /// `priority` for that one `import *` statement. `priority` is the statement's source order
+    /// within the importing scope: when two different `import *` statements re-export the same
+    /// name with two different underlying values,
+    /// [`UseDefMapBuilder::record_glob_definition`](super::use_def::UseDefMapBuilder::record_glob_definition)
+    /// needs it to tell "the same statement expanded the name twice" (not a conflict) apart from
+    /// "two different statements both bound it" (ambiguous) -- it doesn't otherwise have a way to
+    /// group the per-name `Definition`s produced by a single statement back together.
+    StarImport { module: ModuleName, priority: u32 },
+}
@@ -0,0 +1,251 @@
+//! Cross-scope name resolution, following Python's LEGB (Local, Enclosing, Global, Builtins) rule.
+//!
+//! [`UseDefMap`](super::use_def::UseDefMap) only answers "which definitions are visible from a use
+//! *within a single scope*"; if [`UseDefMap::use_definitions`](super::use_def::UseDefMap::use_definitions)
+//! comes back empty, or [`UseDefMap::use_may_be_unbound`](super::use_def::UseDefMap::use_may_be_unbound)
+//! is true, the name might still be bound in an enclosing scope, the module's global scope, or as a
+//! builtin. This is exactly the outward walk rustc's resolver does over a chain of scope "ribs" when
+//! a name isn't found locally, so we follow the same shape here.
+//!
+//! The one rule that makes this more than a straight walk up [`ScopeId::parent`] is that **class
+//! scopes are not part of the LEGB chain for their nested functions**: a method can't see a name
+//! bound in its class body just because it's lexically nested inside it (it would need `self.name`
+//! or an explicit nonlocal/global declaration instead). So [`enclosing_scopes`] filters out any
+//! intervening class scopes while walking outward, while still stopping permanently once it reaches
+//! the module scope (module scopes are never skipped, even though they're also not a function).
+//!
+//! [`resolve_outward`] (and the [`resolve_builtin`] fallback it ends in) isn't called from type
+//! inference yet; that wiring lands with the rest of the name-resolution integration. Both
+//! functions are otherwise complete: [`resolve_builtin`] resolves the vendored `builtins` module
+//! via [`builtins_scope`](crate::module_resolver::builtins::builtins_scope) the same way
+//! [`resolve_outward`] resolves any other enclosing scope.
+//!
+//! [`resolve_outward`] itself needs a live `Db` and a built scope tree to call, which this crate
+//! fragment has no test fixture for; its per-scope stop/fall-through/keep-walking decision is
+//! pulled out into the standalone, generic [`outward_step`] instead (see its doc comment), so that
+//! decision -- including the exact nested-possibly-unbound scenario this module's tests cover --
+//! is exercised directly rather than going untested until that fixture exists.
+
+use crate::semantic_index::definition::Definition;
+use crate::semantic_index::symbol::{ScopeId, ScopeKind, ScopedSymbolId};
+use crate::semantic_index::{symbol_table, use_def_map};
+use crate::Db;
+
+/// The outcome of resolving a name that has no (or only a possibly-unbound) local binding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ResolvedName<'db> {
+    /// Resolved in some enclosing scope (the innermost one that binds it, skipping class scopes).
+    Definitions(Vec<Definition<'db>>),
+
+    /// Not bound in any enclosing scope, but it names a builtin.
+    Builtin,
+
+    /// Not bound anywhere in the LEGB chain; callers should emit an "undefined name" diagnostic.
+    Unresolved,
+}
+
+/// Resolve `symbol_name` outward from `scope`, per LEGB: enclosing function/module scopes first
+/// (skipping class scopes), then builtins.
+///
+/// This should only be called once the local scope itself has already been checked (via
+/// [`UseDefMap::use_definitions`](super::use_def::UseDefMap::use_definitions)) and found lacking;
+/// it does not look at `scope` itself, only its ancestors.
+pub(crate) fn resolve_outward<'db>(
+    db: &'db dyn Db,
+    scope: ScopeId<'db>,
+    symbol_name: &str,
+) -> ResolvedName<'db> {
+    for candidate in enclosing_scopes(db, scope) {
+        let is_module_scope = candidate.kind(db) == ScopeKind::Module;
+
+        let symbol = symbol_table(db, candidate).symbol_id_by_name(symbol_name);
+        let definitions = symbol
+            .map(|symbol| use_def_map(db, candidate).public_definitions(symbol).collect())
+            .unwrap_or_default();
+
+        match outward_step(is_module_scope, symbol.is_some(), definitions) {
+            OutwardStep::Stop(definitions) => return ResolvedName::Definitions(definitions),
+            OutwardStep::FallThroughToBuiltins => return resolve_builtin(db, symbol_name),
+            OutwardStep::KeepWalking => continue,
+        }
+    }
+
+    resolve_builtin(db, symbol_name)
+}
+
+/// The decision made after inspecting one candidate scope's symbol table and use-def map while
+/// walking outward: stop here, fall through to builtins, or keep walking.
+///
+/// Pulled out as a pure function of already-fetched facts (generic over the definitions payload
+/// rather than concretely `Vec<Definition<'db>>`), the same way
+/// [`cycle_resolution::resolve_import_cycle`](crate::module_resolver::cycle_resolution::resolve_import_cycle)
+/// pulls its worklist algorithm out from behind Salsa, so this is unit-testable without a live
+/// `Db`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OutwardStep<T> {
+    /// `candidate` binds the name (at least one of its definitions is visible at the end of the
+    /// scope): for a function scope that ends the LEGB walk here outright, even if every path
+    /// through the scope only *possibly* binds it
+    /// (Python doesn't skip a possibly-unbound enclosing binding looking for a definite one
+    /// further out -- a `NameError`/`UnboundLocalError` at runtime there is still a `NameError`,
+    /// not silently falling back to an outer scope). For the module scope specifically, this
+    /// means the walk's final answer, full stop (see [`Self::FallThroughToBuiltins`] for the one
+    /// case that isn't).
+    Stop(Vec<T>),
+
+    /// The module scope doesn't bind the name at all; builtins is the only place left to look.
+    FallThroughToBuiltins,
+
+    /// `candidate` (not the module scope) doesn't bind the name at all; keep walking outward.
+    KeepWalking,
+}
+
+fn outward_step<T>(is_module_scope: bool, symbol_found: bool, definitions: Vec<T>) -> OutwardStep<T> {
+    if !symbol_found || definitions.is_empty() {
+        return if is_module_scope {
+            OutwardStep::FallThroughToBuiltins
+        } else {
+            OutwardStep::KeepWalking
+        };
+    }
+    OutwardStep::Stop(definitions)
+}
+
+/// Scopes to check, in outward order, skipping class scopes (they aren't part of the LEGB chain
+/// for their nested functions) but never skipping the module scope.
+fn enclosing_scopes<'db>(
+    db: &'db dyn Db,
+    scope: ScopeId<'db>,
+) -> impl Iterator<Item = ScopeId<'db>> + 'db {
+    std::iter::successors(scope.parent(db), move |scope| scope.parent(db))
+        .filter(move |scope| scope.kind(db) != ScopeKind::Class)
+}
+
+/// Does a `nonlocal symbol_name` declaration in `scope` name a real enclosing binding?
+///
+/// Unlike [`resolve_outward`], this stops before the module scope: `nonlocal` explicitly excludes
+/// the global scope (that's what `global` is for), so a `nonlocal x` with no enclosing *function*
+/// binding of `x` is invalid even if the module scope itself binds `x`. `SemanticIndexBuilder`
+/// should call this when it visits a `nonlocal` statement and report
+/// [`NonlocalBindingError::NoEnclosingBinding`] as a `SyntaxError` diagnostic, the same as CPython
+/// does for `no binding for nonlocal 'x' found`.
+pub(crate) fn validate_nonlocal_target<'db>(
+    db: &'db dyn Db,
+    scope: ScopeId<'db>,
+    symbol_name: &str,
+) -> Result<(), NonlocalBindingError> {
+    for candidate in enclosing_scopes(db, scope) {
+        if candidate.kind(db) == ScopeKind::Module {
+            // `nonlocal` never binds in the module scope, no matter what it contains.
+            break;
+        }
+        let Some(symbol) = symbol_table(db, candidate).symbol_id_by_name(symbol_name) else {
+            continue;
+        };
+        if use_def_map(db, candidate)
+            .public_definitions(symbol)
+            .next()
+            .is_some()
+        {
+            return Ok(());
+        }
+    }
+    Err(NonlocalBindingError::NoEnclosingBinding)
+}
+
+/// Why a `nonlocal` declaration is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NonlocalBindingError {
+    /// No enclosing function scope (skipping class scopes, and never the module scope) binds this
+    /// name at all.
+    NoEnclosingBinding,
+}
+
+/// Look up `symbol_name` among the builtins (from the vendored typeshed `builtins.pyi`, resolved
+/// the same way any other module would be via [`ResolverContext`](crate::module_resolver::state::ResolverContext)).
+fn resolve_builtin<'db>(db: &'db dyn Db, symbol_name: &str) -> ResolvedName<'db> {
+    let Some(builtins_scope) = crate::module_resolver::builtins::builtins_scope(db) else {
+        // No vendored `builtins` module to resolve (a malformed or missing typeshed search
+        // path); conservatively treat every name as non-builtin rather than guess.
+        return ResolvedName::Unresolved;
+    };
+
+    let Some(symbol) = symbol_table(db, builtins_scope).symbol_id_by_name(symbol_name) else {
+        return ResolvedName::Unresolved;
+    };
+
+    let use_def_map = use_def_map(db, builtins_scope);
+    if use_def_map.public_definitions(symbol).next().is_none() {
+        // Declared somewhere in `builtins.pyi` (e.g. under a version/platform guard we don't
+        // model) but never actually bound on any path we tracked; treat it the same as absent.
+        return ResolvedName::Unresolved;
+    }
+
+    ResolvedName::Builtin
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{outward_step, OutwardStep};
+
+    #[test]
+    fn stops_at_non_module_scope_even_if_possibly_unbound() {
+        // A non-module candidate that binds the name ends the walk there, regardless of whether
+        // every path through it actually reaches the binding.
+        assert_eq!(
+            outward_step(false, true, vec!["enclosing function's definition"]),
+            OutwardStep::Stop(vec!["enclosing function's definition"]),
+        );
+    }
+
+    #[test]
+    fn keeps_walking_past_a_non_module_scope_without_the_symbol() {
+        assert_eq!(
+            outward_step::<&str>(false, false, vec![]),
+            OutwardStep::KeepWalking,
+        );
+    }
+
+    #[test]
+    fn module_scope_with_a_definition_stops_there() {
+        assert_eq!(
+            outward_step(true, true, vec!["module's definition"]),
+            OutwardStep::Stop(vec!["module's definition"]),
+        );
+    }
+
+    #[test]
+    fn module_scope_without_the_symbol_falls_through_to_builtins() {
+        assert_eq!(
+            outward_step::<&str>(true, false, vec![]),
+            OutwardStep::FallThroughToBuiltins,
+        );
+    }
+
+    #[test]
+    fn module_scope_with_the_symbol_but_no_definitions_falls_through_to_builtins() {
+        // Declared in the module's symbol table (e.g. via a `global` statement somewhere) but
+        // never actually bound on any path: the same as not being there at all.
+        assert_eq!(
+            outward_step::<&str>(true, true, vec![]),
+            OutwardStep::FallThroughToBuiltins,
+        );
+    }
+
+    /// The scenario from the bug this module's `outward_step` logic fixes: `inner()` is nested in
+    /// `outer()`, which only *possibly* binds `x` (e.g. inside an `if`); the module itself also
+    /// binds `x`. Walking outward from `inner`'s scope must stop at `outer`'s possibly-unbound
+    /// binding and never reach the module's, even though `outer`'s binding isn't definite.
+    #[test]
+    fn simulated_walk_prefers_possibly_unbound_enclosing_function_over_module_binding() {
+        // First candidate scope walking outward from `inner`: `outer`'s function scope.
+        let step = outward_step(false, true, vec!["outer's x = 2"]);
+        let OutwardStep::Stop(definitions) = step else {
+            panic!("expected the walk to stop at outer's scope, got {step:?}");
+        };
+        assert_eq!(definitions, vec!["outer's x = 2"]);
+        // The module scope (with its own `x = 1`) is never even consulted: the loop in
+        // `resolve_outward` returns as soon as a scope's step is `Stop`, so a correct
+        // implementation can't reach it from here.
+    }
+}
@@ -122,9 +122,12 @@
 //! [`SemanticIndexBuilder`](crate::semantic_index::builder::SemanticIndexBuilder), e.g. where it
 //! visits a `StmtIf` node.
 //!
-//! (In the future we may have some other questions we want to answer as well, such as "is this
-//! definition used?", which will require tracking a bit more info in our map, e.g. a "used" bit
-//! for each [`Definition`] which is flipped to true when we record that definition for a use.)
+//! We also answer a third question: "is this definition used?" We track a "used" bit for each
+//! [`Definition`], flipped to true when we record that definition for a use (see
+//! [`UseDefMapBuilder::record_use`]), and a "shadowed before use" bit for a definition that gets
+//! replaced by a new definition of the same symbol while its "used" bit is still false (see
+//! [`UseDefMapBuilder::record_definition`]). [`UseDefMap::unused_definitions`] exposes both, for
+//! unused-variable and unused-import diagnostics.
 use self::constrained_definition::{
     ConstrainedDefinitions, ScopedConstraintId, ScopedDefinitionId,
 };
@@ -133,9 +136,12 @@ use crate::semantic_index::definition::Definition;
 use crate::semantic_index::expression::Expression;
 use crate::semantic_index::symbol::ScopedSymbolId;
 use ruff_index::IndexVec;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 mod bitset;
+mod chunked_bitset;
 mod constrained_definition;
+mod interval;
 
 /// All definitions that can reach a given use of a name.
 #[derive(Debug, PartialEq, Eq)]
@@ -151,9 +157,62 @@ pub(crate) struct UseDefMap<'db> {
 
     /// Definitions of each symbol visible at end of scope.
     public_definitions: IndexVec<ScopedSymbolId, ConstrainedDefinitions>,
+
+    /// Symbols left ambiguous because two different `from x import *` statements bound the same
+    /// name to different underlying definitions. See [`UseDefMapBuilder::record_glob_definition`].
+    ambiguous_glob_symbols: FxHashSet<ScopedSymbolId>,
+
+    /// Whether each [`Definition`] in `all_definitions` was ever reached from a recorded
+    /// [`ScopedUseId`] use. Surviving to `public_definitions` does *not* by itself count as used;
+    /// see [`Self::unused_definitions`].
+    used_definitions: IndexVec<ScopedDefinitionId, bool>,
+
+    /// Definitions that were replaced by another definition of the same symbol before any use
+    /// ever reached them -- candidate "dead store" diagnostics, as opposed to definitions that
+    /// simply went unread for the rest of the scope. See [`UseDefMapBuilder::record_definition`].
+    shadowed_before_use: FxHashSet<ScopedDefinitionId>,
+}
+
+/// Why a [`Definition`] returned by [`UseDefMap::unused_definitions`] was never used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UnusedDefinitionKind {
+    /// Replaced by another definition of the same symbol before any use ever reached it (e.g. `x
+    /// = 1` immediately followed by `x = 2`, with no use of `x` in between).
+    DeadStore,
+
+    /// Never replaced, but also never read for the rest of the scope (e.g. an unused import, or a
+    /// variable assigned once and never referenced again).
+    NeverUsed,
 }
 
 impl<'db> UseDefMap<'db> {
+    /// Is `symbol`'s public binding ambiguous due to conflicting `import *` statements?
+    ///
+    /// Callers should use this to decide whether to emit an "ambiguous due to multiple `import *`"
+    /// diagnostic in preference to (or in addition to) looking at [`Self::public_definitions`],
+    /// since the latter only reflects whichever glob import happened to be recorded last.
+    pub(crate) fn public_is_ambiguous_glob(&self, symbol: ScopedSymbolId) -> bool {
+        self.ambiguous_glob_symbols.contains(&symbol)
+    }
+
+    /// Enumerate definitions that were recorded but never reached from any use in this scope,
+    /// along with why ([`UnusedDefinitionKind`]).
+    pub(crate) fn unused_definitions(
+        &self,
+    ) -> impl Iterator<Item = (Definition<'db>, UnusedDefinitionKind)> + '_ {
+        self.all_definitions
+            .iter_enumerated()
+            .filter(|(def_id, _)| !self.used_definitions[*def_id])
+            .map(|(def_id, &definition)| {
+                let kind = if self.shadowed_before_use.contains(&def_id) {
+                    UnusedDefinitionKind::DeadStore
+                } else {
+                    UnusedDefinitionKind::NeverUsed
+                };
+                (definition, kind)
+            })
+    }
+
     pub(crate) fn use_definitions(
         &self,
         use_id: ScopedUseId,
@@ -187,6 +246,38 @@ pub(super) struct FlowSnapshot {
     definitions_by_symbol: IndexVec<ScopedSymbolId, ConstrainedDefinitions>,
 }
 
+/// Which scope does an assignment to a symbol in the current scope actually bind in?
+///
+/// Defaults to [`Local`](ScopedBindingKind::Local); a `global x` or `nonlocal x` declaration
+/// redirects it, the same way rustc_resolve decides which "rib" owns a name. A symbol can only
+/// have one binding kind per scope (Python rejects `global x` after `x` has already been assigned
+/// locally, and vice versa), so [`UseDefMapBuilder::declare_binding_kind`] keeps the first kind
+/// recorded and rejects a conflicting later declaration with a [`ConflictingBindingKind`] error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(super) enum ScopedBindingKind {
+    /// Bind locally, as normal.
+    #[default]
+    Local,
+
+    /// `global x`: binds in the module scope instead of here.
+    Global,
+
+    /// `nonlocal x`: binds in the nearest enclosing function scope instead of here (skipping
+    /// class scopes, same as LEGB lookup does).
+    Nonlocal,
+}
+
+/// `symbol` was declared `global`/`nonlocal` with `found`, but this scope had already recorded a
+/// conflicting `declared` kind for it -- e.g. `nonlocal x` after `global x`, or either one after
+/// `x` has already been used as a plain local binding. [`SemanticIndexBuilder`](super::builder::SemanticIndexBuilder)
+/// should report this as a `SyntaxError` diagnostic at the `found` declaration, the same as
+/// CPython's compiler does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct ConflictingBindingKind {
+    pub(super) declared: ScopedBindingKind,
+    pub(super) found: ScopedBindingKind,
+}
+
 #[derive(Debug, Default)]
 pub(super) struct UseDefMapBuilder<'db> {
     /// Append-only array of [`Definition`]; None is unbound.
@@ -200,6 +291,43 @@ pub(super) struct UseDefMapBuilder<'db> {
 
     /// Currently visible definitions for each symbol.
     definitions_by_symbol: IndexVec<ScopedSymbolId, ConstrainedDefinitions>,
+
+    /// Symbols declared `global`/`nonlocal` in this scope; absence means [`Local`](ScopedBindingKind::Local).
+    binding_kinds: FxHashMap<ScopedSymbolId, ScopedBindingKind>,
+
+    /// Symbols [`Self::record_definition`] has already been called for in this scope, i.e. that
+    /// have already been bound as a plain local. Consulted by [`Self::declare_binding_kind`]: a
+    /// `global`/`nonlocal` declaration reaching a symbol already in this set is declared too late,
+    /// the same way CPython rejects `x = 1; global x`.
+    locally_bound: FxHashSet<ScopedSymbolId>,
+
+    /// Where each symbol's current binding came from, to implement `from x import *` precedence
+    /// (explicit bindings shadow glob ones; two different globs for the same name are ambiguous).
+    binding_origins: FxHashMap<ScopedSymbolId, BindingOrigin<'db>>,
+
+    /// Symbols left ambiguous by two different `import *` statements binding the same name to
+    /// different underlying definitions.
+    ambiguous_glob_symbols: FxHashSet<ScopedSymbolId>,
+
+    /// Whether each [`Definition`] in `all_definitions` has been reached from a recorded use yet.
+    /// Kept in lockstep with `all_definitions`: every push to one is paired with a push to the
+    /// other. See [`Self::record_use`].
+    used_definitions: IndexVec<ScopedDefinitionId, bool>,
+
+    /// Definitions replaced by a new definition of the same symbol before any use ever reached
+    /// them. See [`Self::record_definition`].
+    shadowed_before_use: FxHashSet<ScopedDefinitionId>,
+}
+
+/// Where a symbol's current binding came from, for resolving `from x import *` precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BindingOrigin<'db> {
+    /// An explicit local definition or explicit (non-star) import.
+    Explicit,
+
+    /// A `from module import *`, carrying the definition it contributed so a later, different,
+    /// glob import of the same name can be detected as ambiguous.
+    Glob(Definition<'db>),
 }
 
 impl<'db> UseDefMapBuilder<'db> {
@@ -214,30 +342,155 @@ impl<'db> UseDefMapBuilder<'db> {
         debug_assert_eq!(symbol, new_symbol);
     }
 
+    /// Record that `symbol` was declared `global` or `nonlocal` in this scope.
+    ///
+    /// Does not itself move any definitions: [`SemanticIndexBuilder`](super::builder::SemanticIndexBuilder)
+    /// consults [`Self::binding_kind`] and forwards subsequent `record_definition`/`record_use`
+    /// calls for this symbol to the target scope's builder (the module scope for `Global`, or the
+    /// nearest enclosing non-class scope's builder for `Nonlocal`) instead of calling them here.
+    ///
+    /// Returns `Err` if `symbol` already has a *different* recorded kind in this scope (e.g.
+    /// `nonlocal x` after `global x`, or either one after `x` has already been bound as a plain
+    /// local): Python rejects that combination as a syntax error, and `SemanticIndexBuilder` should
+    /// report it as one rather than silently picking a kind. Declaring the same kind twice (e.g.
+    /// two `global x` statements in one scope, which CPython itself allows) is not an error.
+    pub(super) fn declare_binding_kind(
+        &mut self,
+        symbol: ScopedSymbolId,
+        kind: ScopedBindingKind,
+    ) -> Result<(), ConflictingBindingKind> {
+        debug_assert_ne!(kind, ScopedBindingKind::Local);
+        if let Some(&existing) = self.binding_kinds.get(&symbol) {
+            if existing != kind {
+                return Err(ConflictingBindingKind {
+                    declared: existing,
+                    found: kind,
+                });
+            }
+            return Ok(());
+        }
+        if self.locally_bound.contains(&symbol) {
+            return Err(ConflictingBindingKind {
+                declared: ScopedBindingKind::Local,
+                found: kind,
+            });
+        }
+        self.binding_kinds.insert(symbol, kind);
+        Ok(())
+    }
+
+    /// How does an assignment to `symbol` in this scope actually bind?
+    pub(super) fn binding_kind(&self, symbol: ScopedSymbolId) -> ScopedBindingKind {
+        self.binding_kinds
+            .get(&symbol)
+            .copied()
+            .unwrap_or_default()
+    }
+
     pub(super) fn record_definition(
         &mut self,
         symbol: ScopedSymbolId,
         definition: Definition<'db>,
     ) {
         // We have a new definition of a symbol; this replaces any previous definitions in this
-        // path.
+        // path. An explicit definition always wins over (and resolves the ambiguity of) any glob
+        // import that bound this symbol previously.
+        self.locally_bound.insert(symbol);
+        self.mark_shadowed_if_unused(symbol);
+        let def_id = self.all_definitions.push(definition);
+        self.used_definitions.push(false);
+        self.definitions_by_symbol[symbol] = ConstrainedDefinitions::with(def_id);
+        self.binding_origins.insert(symbol, BindingOrigin::Explicit);
+        self.ambiguous_glob_symbols.remove(&symbol);
+    }
+
+    /// Before overwriting `symbol`'s currently-visible definitions, record any of them that were
+    /// never reached by a use as "shadowed before use" (a candidate dead store).
+    fn mark_shadowed_if_unused(&mut self, symbol: ScopedSymbolId) {
+        for previous in self.definitions_by_symbol[symbol].iter_visible_definitions() {
+            if !self.used_definitions[previous.definition] {
+                self.shadowed_before_use.insert(previous.definition);
+            }
+        }
+    }
+
+    /// Record a definition for `symbol` contributed by a `from module import *` statement.
+    ///
+    /// Honors `import *` precedence: an explicit definition already recorded for `symbol` (a local
+    /// assignment, or an explicit import) shadows this glob binding outright, so it's left alone.
+    /// Otherwise, if `symbol` is already glob-bound to a *different* definition (from an earlier,
+    /// different, star import), the conflict can't be silently resolved, so `symbol` is flagged
+    /// ambiguous; a repeat of the *same* definition is not a conflict. The comparison is between
+    /// the `StarImport` definitions themselves, which carry each statement's `module` and
+    /// `priority`: two different `import *` statements binding the same name are always flagged
+    /// ambiguous, even when both re-export the same underlying definition.
+    ///
+    /// Called once per exported name by
+    /// [`expand_star_import`](super::builder::expand_star_import), which is what actually visits a
+    /// `from module import *` statement and builds the per-name
+    /// [`DefinitionKind::StarImport`](super::definition::DefinitionKind::StarImport) `Definition`s
+    /// this expects.
+    pub(super) fn record_glob_definition(
+        &mut self,
+        symbol: ScopedSymbolId,
+        definition: Definition<'db>,
+    ) {
+        match self.binding_origins.get(&symbol) {
+            Some(BindingOrigin::Explicit) => return,
+            Some(BindingOrigin::Glob(existing)) if *existing != definition => {
+                self.ambiguous_glob_symbols.insert(symbol);
+                return;
+            }
+            _ => {}
+        }
+        self.mark_shadowed_if_unused(symbol);
         let def_id = self.all_definitions.push(definition);
+        self.used_definitions.push(false);
         self.definitions_by_symbol[symbol] = ConstrainedDefinitions::with(def_id);
+        self.binding_origins
+            .insert(symbol, BindingOrigin::Glob(definition));
     }
 
     pub(super) fn record_constraint(&mut self, constraint: Expression<'db>) {
-        let constraint_id = self.all_constraints.push(constraint);
-        for definitions in &mut self.definitions_by_symbol {
-            definitions.add_constraint(constraint_id);
+        self.record_constraints(std::slice::from_ref(&constraint));
+    }
+
+    /// Record a batch of constraints that all apply simultaneously to every currently-visible
+    /// definition -- e.g. the prior conditions that must all have been false to reach an
+    /// `elif`/`else` branch, as [`builder::enter_elif_or_else`](super::builder::enter_elif_or_else)
+    /// does. Pushed together, the batch ends up as a contiguous run of [`ScopedConstraintId`]s, so
+    /// it can be applied to each definition with a single
+    /// [`ConstrainedDefinitions::add_constraint_range`] call instead of one
+    /// [`ConstrainedDefinitions::add_constraint`] pass per constraint.
+    pub(super) fn record_constraints(&mut self, constraints: &[Expression<'db>]) {
+        let Some((&first, rest)) = constraints.split_first() else {
+            return;
+        };
+        let start = self.all_constraints.push(first);
+        let mut end = start;
+        for &constraint in rest {
+            end = self.all_constraints.push(constraint);
+        }
+
+        if start == end {
+            for definitions in &mut self.definitions_by_symbol {
+                definitions.add_constraint(start);
+            }
+        } else {
+            for definitions in &mut self.definitions_by_symbol {
+                definitions.add_constraint_range(start, end);
+            }
         }
     }
 
     pub(super) fn record_use(&mut self, symbol: ScopedSymbolId, use_id: ScopedUseId) {
         // We have a use of a symbol; clone the currently visible definitions for that symbol, and
         // record them as the visible definitions for this use.
-        let new_use = self
-            .definitions_by_use
-            .push(self.definitions_by_symbol[symbol].clone());
+        let visible = &self.definitions_by_symbol[symbol];
+        for visible_definition in visible.iter_visible_definitions() {
+            self.used_definitions[visible_definition.definition] = true;
+        }
+        let new_use = self.definitions_by_use.push(visible.clone());
         debug_assert_eq!(use_id, new_use);
     }
 
@@ -299,12 +552,108 @@ impl<'db> UseDefMapBuilder<'db> {
         self.all_constraints.shrink_to_fit();
         self.definitions_by_symbol.shrink_to_fit();
         self.definitions_by_use.shrink_to_fit();
+        self.used_definitions.shrink_to_fit();
+        self.shadowed_before_use.shrink_to_fit();
 
         UseDefMap {
             all_definitions: self.all_definitions,
             all_constraints: self.all_constraints,
             definitions_by_use: self.definitions_by_use,
             public_definitions: self.definitions_by_symbol,
+            ambiguous_glob_symbols: self.ambiguous_glob_symbols,
+            used_definitions: self.used_definitions,
+            shadowed_before_use: self.shadowed_before_use,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ScopedBindingKind, UseDefMapBuilder};
+    use crate::semantic_index::symbol::ScopedSymbolId;
+
+    fn symbol(index: u32) -> ScopedSymbolId {
+        ScopedSymbolId::from_u32(index)
+    }
+
+    #[test]
+    fn binding_kind_defaults_to_local() {
+        let builder = UseDefMapBuilder::new();
+
+        assert_eq!(builder.binding_kind(symbol(0)), ScopedBindingKind::Local);
+    }
+
+    #[test]
+    fn declare_binding_kind_global() {
+        let mut builder = UseDefMapBuilder::new();
+
+        builder
+            .declare_binding_kind(symbol(0), ScopedBindingKind::Global)
+            .unwrap();
+
+        assert_eq!(builder.binding_kind(symbol(0)), ScopedBindingKind::Global);
+        // A different symbol in the same scope is unaffected.
+        assert_eq!(builder.binding_kind(symbol(1)), ScopedBindingKind::Local);
+    }
+
+    #[test]
+    fn declare_binding_kind_nonlocal() {
+        let mut builder = UseDefMapBuilder::new();
+
+        builder
+            .declare_binding_kind(symbol(0), ScopedBindingKind::Nonlocal)
+            .unwrap();
+
+        assert_eq!(builder.binding_kind(symbol(0)), ScopedBindingKind::Nonlocal);
+    }
+
+    #[test]
+    fn declare_binding_kind_repeated_same_kind_is_fine() {
+        let mut builder = UseDefMapBuilder::new();
+
+        builder
+            .declare_binding_kind(symbol(0), ScopedBindingKind::Global)
+            .unwrap();
+        builder
+            .declare_binding_kind(symbol(0), ScopedBindingKind::Global)
+            .unwrap();
+
+        assert_eq!(builder.binding_kind(symbol(0)), ScopedBindingKind::Global);
+    }
+
+    #[test]
+    fn declare_binding_kind_conflicting_kind_is_an_error() {
+        let mut builder = UseDefMapBuilder::new();
+
+        builder
+            .declare_binding_kind(symbol(0), ScopedBindingKind::Global)
+            .unwrap();
+        let conflict = builder
+            .declare_binding_kind(symbol(0), ScopedBindingKind::Nonlocal)
+            .unwrap_err();
+
+        assert_eq!(conflict.declared, ScopedBindingKind::Global);
+        assert_eq!(conflict.found, ScopedBindingKind::Nonlocal);
+        // The earlier declaration is left in place; the builder doesn't overwrite it on a
+        // conflicting second declaration.
+        assert_eq!(builder.binding_kind(symbol(0)), ScopedBindingKind::Global);
+    }
+
+    #[test]
+    fn declare_binding_kind_after_local_binding_is_an_error() {
+        // `x = 1; global x`: a real `record_definition(symbol, ...)` call needs a `Definition`,
+        // which needs a `Db`, which this module's pure data-structure tests don't have a fixture
+        // for; `locally_bound` is exactly the piece of state `record_definition` updates, so
+        // setting it directly exercises the same contract `declare_binding_kind` checks.
+        let mut builder = UseDefMapBuilder::new();
+        builder.locally_bound.insert(symbol(0));
+
+        let conflict = builder
+            .declare_binding_kind(symbol(0), ScopedBindingKind::Global)
+            .unwrap_err();
+
+        assert_eq!(conflict.declared, ScopedBindingKind::Local);
+        assert_eq!(conflict.found, ScopedBindingKind::Global);
+        assert_eq!(builder.binding_kind(symbol(0)), ScopedBindingKind::Local);
+    }
+}
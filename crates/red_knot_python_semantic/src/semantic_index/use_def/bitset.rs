@@ -1,26 +1,62 @@
-use std::collections::{btree_set, BTreeSet};
+use std::marker::PhantomData;
 
-/// Ordered set of `u32`; bit-set for small values (up to 128 * B), BTreeSet for overflow.
+use super::chunked_bitset::{ChunkedBitSet, ChunkedBitSetIterator};
+
+/// A type that can be used as the element of a [`BitSet`]/[`BitSetArray`].
+///
+/// Mirrors rustc_index's `Idx` trait, pared down to just what the bitsets need: a value can be
+/// converted to and from a dense `usize` index.
+pub(super) trait Idx: Copy + Eq {
+    fn index(&self) -> usize;
+    fn from_usize(value: usize) -> Self;
+}
+
+impl Idx for u32 {
+    fn index(&self) -> usize {
+        *self as usize
+    }
+
+    fn from_usize(value: usize) -> Self {
+        value as u32
+    }
+}
+
+/// Relations that can be applied in-place between two sets of the same shape.
+///
+/// Mirrors rustc_index's `BitRelations` trait: `union` (called `merge` here to match the existing
+/// naming in this module), `intersect`, and `subtract`, each returning whether `self` changed.
+pub(super) trait BitRelations<Rhs> {
+    /// Merge (union) `other` into `self`. Return true if `self` changed.
+    fn merge(&mut self, other: &Rhs) -> bool;
+
+    /// Intersect `self` with `other` in place. Return true if `self` changed.
+    fn intersect(&mut self, other: &Rhs) -> bool;
+
+    /// Remove every member of `other` from `self`. Return true if `self` changed.
+    fn subtract(&mut self, other: &Rhs) -> bool;
+}
+
+/// Ordered set of `I`; bit-set for small values (up to 128 * B), chunked dense set for overflow.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(super) enum BitSet<const B: usize> {
+pub(super) enum BitSet<I, const B: usize> {
     /// Bit-set (in 128-bit blocks) for the first 128 * B entries.
-    Blocks([u128; B]),
+    Blocks([u128; B], PhantomData<I>),
 
     /// Overflow beyond 128 * B.
-    Overflow(BTreeSet<u32>),
+    Overflow(ChunkedBitSet, PhantomData<I>),
 }
 
-impl<const B: usize> Default for BitSet<B> {
+impl<I, const B: usize> Default for BitSet<I, B> {
     fn default() -> Self {
-        Self::Blocks([0; B])
+        Self::Blocks([0; B], PhantomData)
     }
 }
 
-impl<const B: usize> BitSet<B> {
+impl<I: Idx, const B: usize> BitSet<I, B> {
     const BITS: u32 = (128 * B) as u32;
 
     /// Create and return a new BitSet with a single `value` inserted.
-    pub(super) fn with(value: u32) -> Self {
+    pub(super) fn with(value: I) -> Self {
         let mut bitset = Self::default();
         bitset.insert(value);
         bitset
@@ -28,90 +64,211 @@ impl<const B: usize> BitSet<B> {
 
     /// Convert from Blocks to Overflow representation.
     fn overflow(&mut self) {
-        if matches!(self, Self::Blocks(_)) {
-            let set = BTreeSet::from_iter(self.iter());
-            *self = Self::Overflow(set);
+        if matches!(self, Self::Blocks(..)) {
+            let mut set = ChunkedBitSet::default();
+            for value in self.iter() {
+                set.insert(value.index() as u32);
+            }
+            *self = Self::Overflow(set, PhantomData);
         }
     }
 
     /// Insert a value into the BitSet.
     ///
     /// Return true if the value was newly inserted, false if already present.
-    pub(super) fn insert(&mut self, value: u32) -> bool {
+    pub(super) fn insert(&mut self, value: I) -> bool {
+        let value = value.index() as u32;
         if value >= Self::BITS {
             self.overflow();
         }
         match self {
-            Self::Blocks(blocks) => {
+            Self::Blocks(blocks, _) => {
                 let value_usize = value as usize;
                 let (block, index) = (value_usize / 128, value_usize % 128);
                 let missing = blocks[block] & (1_u128 << index) == 0;
                 blocks[block] |= 1_u128 << index;
                 missing
             }
-            Self::Overflow(set) => set.insert(value),
+            Self::Overflow(set, _) => set.insert(value),
+        }
+    }
+
+    /// Remove a single value from the BitSet.
+    ///
+    /// Return true if the value was present and removed, false if it was already absent.
+    fn remove(&mut self, value: I) -> bool {
+        let value = value.index() as u32;
+        match self {
+            Self::Blocks(blocks, _) => {
+                if value >= Self::BITS {
+                    return false;
+                }
+                let value_usize = value as usize;
+                let (block, index) = (value_usize / 128, value_usize % 128);
+                let present = blocks[block] & (1_u128 << index) != 0;
+                blocks[block] &= !(1_u128 << index);
+                present
+            }
+            Self::Overflow(set, _) => set.remove(value),
+        }
+    }
+
+    /// If we're in the `Overflow` representation but no longer need to be, demote back to
+    /// `Blocks`.
+    fn demote(&mut self) {
+        if let Self::Overflow(set, _) = self {
+            if set.max().map_or(true, |max| max < Self::BITS) {
+                let mut blocks = [0_u128; B];
+                for value in set.iter() {
+                    let value_usize = value as usize;
+                    let (block, index) = (value_usize / 128, value_usize % 128);
+                    blocks[block] |= 1_u128 << index;
+                }
+                *self = Self::Blocks(blocks, PhantomData);
+            }
         }
     }
 
+    /// Return the number of values in this BitSet.
+    pub(super) fn len(&self) -> usize {
+        match self {
+            Self::Blocks(blocks, _) => blocks.iter().map(|b| b.count_ones() as usize).sum(),
+            Self::Overflow(set, _) => set.len(),
+        }
+    }
+
+    /// Return true if this BitSet has no values.
+    pub(super) fn is_empty(&self) -> bool {
+        match self {
+            Self::Blocks(blocks, _) => blocks.iter().all(|&b| b == 0),
+            Self::Overflow(set, _) => set.is_empty(),
+        }
+    }
+
+    /// Return true if `value` is a member of this BitSet.
+    pub(super) fn contains(&self, value: I) -> bool {
+        let value = value.index() as u32;
+        match self {
+            Self::Blocks(blocks, _) => {
+                if value >= Self::BITS {
+                    return false;
+                }
+                let value_usize = value as usize;
+                let (block, index) = (value_usize / 128, value_usize % 128);
+                blocks[block] & (1_u128 << index) != 0
+            }
+            Self::Overflow(set, _) => set.contains(value),
+        }
+    }
+
+    /// Return an iterator over the values (in ascending order) in this BitSet.
+    pub(super) fn iter(&self) -> BitSetIterator<'_, I, B> {
+        match self {
+            Self::Blocks(blocks, _) => BitSetIterator::Blocks {
+                blocks,
+                cur_block_index: 0,
+                cur_block: blocks[0],
+                _idx: PhantomData,
+            },
+            Self::Overflow(set, _) => BitSetIterator::Overflow(set.iter(), PhantomData),
+        }
+    }
+}
+
+impl<I: Idx, const B: usize> BitRelations<BitSet<I, B>> for BitSet<I, B> {
     /// Merge another BitSet into this one.
     ///
     /// Equivalent to (but often more efficient than) iterating the other BitSet and inserting its
     /// values one-by-one into this BitSet.
-    #[allow(dead_code)]
-    pub(super) fn merge(&mut self, other: &BitSet<B>) {
-        match (self, other) {
-            (Self::Blocks(myblocks), Self::Blocks(other_blocks)) => {
+    fn merge(&mut self, other: &BitSet<I, B>) -> bool {
+        let mut changed = false;
+        match (&mut *self, other) {
+            (Self::Blocks(myblocks, _), Self::Blocks(other_blocks, _)) => {
                 for i in 0..B {
-                    myblocks[i] |= other_blocks[i];
+                    let merged = myblocks[i] | other_blocks[i];
+                    changed |= merged != myblocks[i];
+                    myblocks[i] = merged;
                 }
             }
-            (Self::Overflow(myset), Self::Overflow(other_set)) => {
-                myset.extend(other_set);
+            (Self::Overflow(myset, _), Self::Overflow(other_set, _)) => {
+                changed = myset.merge(other_set);
             }
-            (me, other) => {
+            (_, other) => {
                 for value in other.iter() {
-                    me.insert(value);
+                    changed |= self.insert(value);
                 }
             }
         }
+        changed
     }
 
     /// Intersect in-place with another BitSet.
-    pub(super) fn intersect(&mut self, other: &BitSet<B>) {
-        match (self, other) {
-            (Self::Blocks(myblocks), Self::Blocks(other_blocks)) => {
+    fn intersect(&mut self, other: &BitSet<I, B>) -> bool {
+        let mut changed = false;
+        match (&mut *self, other) {
+            (Self::Blocks(myblocks, _), Self::Blocks(other_blocks, _)) => {
                 for i in 0..B {
-                    myblocks[i] &= other_blocks[i];
+                    let intersected = myblocks[i] & other_blocks[i];
+                    changed |= intersected != myblocks[i];
+                    myblocks[i] = intersected;
                 }
             }
-            (Self::Overflow(myset), Self::Overflow(other_set)) => {
-                let intersection = BTreeSet::from_iter(myset.intersection(other_set).copied());
-                *myset = intersection;
+            (Self::Overflow(myset, _), Self::Overflow(other_set, _)) => {
+                changed = myset.intersect(other_set);
             }
             (me, other) => {
-                for value in other.iter() {
-                    me.insert(value);
+                let mut count_before = 0_usize;
+                let mut new_me = Self::default();
+                for value in me.iter() {
+                    count_before += 1;
+                    if other.contains(value) {
+                        new_me.insert(value);
+                    }
                 }
+                changed = new_me.iter().count() != count_before;
+                *me = new_me;
             }
         }
+        self.demote();
+        changed
     }
 
-    /// Return an iterator over the values (in ascending order) in this BitSet.
-    pub(super) fn iter(&self) -> BitSetIterator<'_, B> {
-        match self {
-            Self::Blocks(blocks) => BitSetIterator::Blocks {
-                blocks: &blocks,
-                cur_block_index: 0,
-                cur_block: blocks[0],
-            },
-            Self::Overflow(set) => BitSetIterator::Overflow(set.iter()),
+    /// Remove every member of `other` from `self` in place.
+    ///
+    /// Never promotes `self` to the `Overflow` representation (there's nothing to gain: removing
+    /// members can only shrink the set), and demotes back to `Blocks` if the overflow set drains
+    /// below [`Self::BITS`].
+    fn subtract(&mut self, other: &BitSet<I, B>) -> bool {
+        let mut changed = false;
+        match (&mut *self, other) {
+            (Self::Blocks(myblocks, _), Self::Blocks(other_blocks, _)) => {
+                for i in 0..B {
+                    changed |= myblocks[i] & other_blocks[i] != 0;
+                    myblocks[i] &= !other_blocks[i];
+                }
+            }
+            (Self::Overflow(myset, _), Self::Overflow(other_set, _)) => {
+                changed = myset.subtract(other_set);
+            }
+            (Self::Blocks(..), Self::Overflow(other_set, _)) => {
+                for value in other_set.iter() {
+                    changed |= self.remove(I::from_usize(value as usize));
+                }
+            }
+            (Self::Overflow(myset, _), Self::Blocks(..)) => {
+                for value in other.iter() {
+                    changed |= myset.remove(value.index() as u32);
+                }
+            }
         }
+        self.demote();
+        changed
     }
 }
 
 /// Iterator over values in a [`BitSet`].
 #[derive(Debug)]
-pub(super) enum BitSetIterator<'a, const B: usize> {
+pub(super) enum BitSetIterator<'a, I, const B: usize> {
     Blocks {
         /// The blocks we are iterating over.
         blocks: &'a [u128; B],
@@ -121,12 +278,14 @@ pub(super) enum BitSetIterator<'a, const B: usize> {
 
         /// The block we are currently iterating through (and zeroing as we go.)
         cur_block: u128,
+
+        _idx: PhantomData<I>,
     },
-    Overflow(btree_set::Iter<'a, u32>),
+    Overflow(ChunkedBitSetIterator<'a>, PhantomData<I>),
 }
 
-impl<const B: usize> Iterator for BitSetIterator<'_, B> {
-    type Item = u32;
+impl<I: Idx, const B: usize> Iterator for BitSetIterator<'_, I, B> {
+    type Item = I;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self {
@@ -134,6 +293,7 @@ impl<const B: usize> Iterator for BitSetIterator<'_, B> {
                 blocks,
                 cur_block_index,
                 cur_block,
+                ..
             } => {
                 while *cur_block == 0 {
                     if *cur_block_index == B - 1 {
@@ -145,30 +305,32 @@ impl<const B: usize> Iterator for BitSetIterator<'_, B> {
                 let value = cur_block.trailing_zeros() as u32;
                 // reset the lowest set bit
                 *cur_block &= cur_block.wrapping_sub(1);
-                Some(value + (128 * (*cur_block_index as u32)))
+                Some(I::from_usize(
+                    (value + (128 * (*cur_block_index as u32))) as usize,
+                ))
             }
-            Self::Overflow(set_iter) => set_iter.next().copied(),
+            Self::Overflow(set_iter, _) => set_iter.next().map(|value| I::from_usize(value as usize)),
         }
     }
 }
 
-impl<const B: usize> std::iter::FusedIterator for BitSetIterator<'_, B> {}
+impl<I: Idx, const B: usize> std::iter::FusedIterator for BitSetIterator<'_, I, B> {}
 
-/// Array of BitSet<B>. Up to N stored inline, more than that in overflow vector.
+/// Array of `BitSet<I, B>`. Up to N stored inline, more than that in overflow vector.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(super) enum BitSetArray<const B: usize, const N: usize> {
+pub(super) enum BitSetArray<I, const B: usize, const N: usize> {
     Array {
         /// Array of N BitSets.
-        array: [BitSet<B>; N],
+        array: [BitSet<I, B>; N],
 
         /// How many of the bitsets are used?
         size: usize,
     },
 
-    Overflow(Vec<BitSet<B>>),
+    Overflow(Vec<BitSet<I, B>>),
 }
 
-impl<const B: usize, const N: usize> Default for BitSetArray<B, N> {
+impl<I: Idx, const B: usize, const N: usize> Default for BitSetArray<I, B, N> {
     fn default() -> Self {
         Self::Array {
             array: std::array::from_fn(|_| BitSet::default()),
@@ -177,7 +339,7 @@ impl<const B: usize, const N: usize> Default for BitSetArray<B, N> {
     }
 }
 
-impl<const B: usize, const N: usize> BitSetArray<B, N> {
+impl<I: Idx, const B: usize, const N: usize> BitSetArray<I, B, N> {
     /// Create a [`BitSetArray`] of `size` empty [`BitSet`]s.
     pub(super) fn of_size(size: usize) -> Self {
         let mut array = Self::default();
@@ -190,7 +352,7 @@ impl<const B: usize, const N: usize> BitSetArray<B, N> {
     fn overflow(&mut self) {
         match self {
             Self::Array { array, size } => {
-                let mut vec: Vec<BitSet<B>> = vec![];
+                let mut vec: Vec<BitSet<I, B>> = vec![];
                 for i in 0..(*size - 1) {
                     vec.push(array[i].clone());
                 }
@@ -201,7 +363,7 @@ impl<const B: usize, const N: usize> BitSetArray<B, N> {
     }
 
     /// Push a [`BitSet`] onto the end of the array.
-    pub(super) fn push(&mut self, new: BitSet<B>) {
+    pub(super) fn push(&mut self, new: BitSet<I, B>) {
         match self {
             Self::Array { array, size } => {
                 *size += 1;
@@ -217,7 +379,7 @@ impl<const B: usize, const N: usize> BitSetArray<B, N> {
     }
 
     /// Return a mutable reference to the last [`BitSet`] in the array, or None.
-    pub(super) fn last_mut(&mut self) -> Option<&mut BitSet<B>> {
+    pub(super) fn last_mut(&mut self) -> Option<&mut BitSet<I, B>> {
         match self {
             Self::Array { array, size } => {
                 if *size == 0 {
@@ -231,7 +393,7 @@ impl<const B: usize, const N: usize> BitSetArray<B, N> {
     }
 
     /// Insert `value` into every [`BitSet`] in this [`BitSetArray`].
-    pub(super) fn insert_in_each(&mut self, value: u32) {
+    pub(super) fn insert_in_each(&mut self, value: I) {
         match self {
             Self::Array { array, size } => {
                 for i in 0..*size {
@@ -246,8 +408,27 @@ impl<const B: usize, const N: usize> BitSetArray<B, N> {
         }
     }
 
+    /// Merge `other` into every [`BitSet`] in this [`BitSetArray`].
+    ///
+    /// Cheaper than calling [`Self::insert_in_each`] once per member of `other` when `other` was
+    /// itself built as a contiguous run (e.g. from an [`super::interval::IntervalSet`] range).
+    pub(super) fn merge_in_each(&mut self, other: &BitSet<I, B>) {
+        match self {
+            Self::Array { array, size } => {
+                for i in 0..*size {
+                    array[i].merge(other);
+                }
+            }
+            Self::Overflow(vec) => {
+                for bitset in vec {
+                    bitset.merge(other);
+                }
+            }
+        }
+    }
+
     /// Return an iterator over each [`BitSet`] in this [`BitSetArray`].
-    pub(super) fn iter(&self) -> BitSetArrayIterator<'_, B, N> {
+    pub(super) fn iter(&self) -> BitSetArrayIterator<'_, I, B, N> {
         match self {
             Self::Array { array, size } => BitSetArrayIterator::Array {
                 array,
@@ -261,18 +442,18 @@ impl<const B: usize, const N: usize> BitSetArray<B, N> {
 
 /// Iterator over a [`BitSetArray`].
 #[derive(Debug)]
-pub(super) enum BitSetArrayIterator<'a, const B: usize, const N: usize> {
+pub(super) enum BitSetArrayIterator<'a, I, const B: usize, const N: usize> {
     Array {
-        array: &'a [BitSet<B>; N],
+        array: &'a [BitSet<I, B>; N],
         index: usize,
         size: usize,
     },
 
-    Overflow(core::slice::Iter<'a, BitSet<B>>),
+    Overflow(core::slice::Iter<'a, BitSet<I, B>>),
 }
 
-impl<'a, const B: usize, const N: usize> Iterator for BitSetArrayIterator<'a, B, N> {
-    type Item = &'a BitSet<B>;
+impl<'a, I: Idx, const B: usize, const N: usize> Iterator for BitSetArrayIterator<'a, I, B, N> {
+    type Item = &'a BitSet<I, B>;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self {
@@ -289,41 +470,44 @@ impl<'a, const B: usize, const N: usize> Iterator for BitSetArrayIterator<'a, B,
     }
 }
 
-impl<const B: usize, const N: usize> std::iter::FusedIterator for BitSetArrayIterator<'_, B, N> {}
+impl<I: Idx, const B: usize, const N: usize> std::iter::FusedIterator
+    for BitSetArrayIterator<'_, I, B, N>
+{
+}
 
 #[cfg(test)]
 mod tests {
-    use super::{BitSet, BitSetArray};
+    use super::{BitRelations, BitSet, BitSetArray};
 
-    fn assert_bitset<const B: usize>(bitset: &BitSet<B>, contents: &[u32]) {
+    fn assert_bitset<const B: usize>(bitset: &BitSet<u32, B>, contents: &[u32]) {
         assert_eq!(bitset.iter().collect::<Vec<_>>(), contents);
     }
 
     mod bitset {
-        use super::{assert_bitset, BitSet};
+        use super::{assert_bitset, BitRelations, BitSet};
 
         #[test]
         fn iter() {
-            let mut b = BitSet::<1>::with(3);
+            let mut b = BitSet::<u32, 1>::with(3);
             b.insert(27);
             b.insert(6);
-            assert!(matches!(b, BitSet::Blocks(_)));
+            assert!(matches!(b, BitSet::Blocks(..)));
             assert_bitset(&b, &[3, 6, 27]);
         }
 
         #[test]
         fn iter_overflow() {
-            let mut b = BitSet::<1>::with(140);
+            let mut b = BitSet::<u32, 1>::with(140);
             b.insert(100);
             b.insert(129);
-            assert!(matches!(b, BitSet::Overflow(_)));
+            assert!(matches!(b, BitSet::Overflow(..)));
             assert_bitset(&b, &[100, 129, 140]);
         }
 
         #[test]
         fn merge() {
-            let mut b1 = BitSet::<1>::with(4);
-            let mut b2 = BitSet::<1>::with(21);
+            let mut b1 = BitSet::<u32, 1>::with(4);
+            let mut b2 = BitSet::<u32, 1>::with(21);
             b1.insert(179);
             b2.insert(130);
             b2.insert(179);
@@ -333,8 +517,8 @@ mod tests {
 
         #[test]
         fn intersect() {
-            let mut b1 = BitSet::<1>::with(4);
-            let mut b2 = BitSet::<1>::with(4);
+            let mut b1 = BitSet::<u32, 1>::with(4);
+            let mut b2 = BitSet::<u32, 1>::with(4);
             b1.insert(23);
             b2.insert(5);
 
@@ -342,17 +526,64 @@ mod tests {
             assert_bitset(&b1, &[4]);
         }
 
+        #[test]
+        fn intersect_mixed_representations() {
+            // One side overflows past 128 bits, the other stays in `Blocks`; `intersect` must take
+            // the mixed branch and still only keep members present in both.
+            let mut blocks_only = BitSet::<u32, 1>::with(4);
+            blocks_only.insert(23);
+
+            let mut overflowed = BitSet::<u32, 1>::with(4);
+            overflowed.insert(23);
+            overflowed.insert(140);
+            assert!(matches!(overflowed, BitSet::Overflow(..)));
+
+            blocks_only.intersect(&overflowed);
+            assert_bitset(&blocks_only, &[4, 23]);
+        }
+
+        #[test]
+        fn subtract() {
+            let mut b1 = BitSet::<u32, 1>::with(4);
+            b1.insert(23);
+            b1.insert(140);
+            let mut b2 = BitSet::<u32, 1>::with(23);
+            b2.insert(140);
+
+            assert!(b1.subtract(&b2));
+            assert_bitset(&b1, &[4]);
+            assert!(matches!(b1, BitSet::Blocks(..)));
+
+            assert!(!b1.subtract(&b2));
+        }
+
+        #[test]
+        fn len_is_empty_contains() {
+            let mut b = BitSet::<u32, 1>::default();
+            assert_eq!(b.len(), 0);
+            assert!(b.is_empty());
+            assert!(!b.contains(4));
+
+            b.insert(4);
+            b.insert(140);
+            assert_eq!(b.len(), 2);
+            assert!(!b.is_empty());
+            assert!(b.contains(4));
+            assert!(b.contains(140));
+            assert!(!b.contains(5));
+        }
+
         #[test]
         fn multiple_blocks() {
-            let mut b = BitSet::<2>::with(130);
+            let mut b = BitSet::<u32, 2>::with(130);
             b.insert(45);
-            assert!(matches!(b, BitSet::Blocks(_)));
+            assert!(matches!(b, BitSet::Blocks(..)));
             assert_bitset(&b, &[45, 130]);
         }
     }
 
     fn assert_array<const B: usize, const N: usize>(
-        array: &BitSetArray<B, N>,
+        array: &BitSetArray<u32, B, N>,
         contents: &[Vec<u32>],
     ) {
         assert_eq!(
@@ -365,11 +596,11 @@ mod tests {
     }
 
     mod bitset_array {
-        use super::{assert_array, BitSet, BitSetArray};
+        use super::{assert_array, BitRelations, BitSet, BitSetArray};
 
         #[test]
         fn insert_in_each() {
-            let mut ba = BitSetArray::<1, 2>::default();
+            let mut ba = BitSetArray::<u32, 1, 2>::default();
             assert_array(&ba, &[]);
 
             ba.push(BitSet::default());
@@ -396,14 +627,14 @@ mod tests {
 
         #[test]
         fn of_size() {
-            let mut ba = BitSetArray::<1, 2>::of_size(1);
+            let mut ba = BitSetArray::<u32, 1, 2>::of_size(1);
             ba.insert_in_each(5);
             assert_array(&ba, &[vec![5]])
         }
 
         #[test]
         fn last_mut() {
-            let mut ba = BitSetArray::<1, 2>::of_size(1);
+            let mut ba = BitSetArray::<u32, 1, 2>::of_size(1);
 
             ba.last_mut()
                 .expect("last to not be None")
@@ -414,7 +645,7 @@ mod tests {
 
         #[test]
         fn last_mut_none() {
-            let mut ba = BitSetArray::<1, 1>::default();
+            let mut ba = BitSetArray::<u32, 1, 1>::default();
 
             assert!(ba.last_mut().is_none());
         }
@@ -0,0 +1,415 @@
+use std::rc::Rc;
+
+use super::bitset::BitRelations;
+
+/// Number of `u128` words per chunk.
+const CHUNK_WORDS: usize = 32;
+
+/// Number of bits covered by a single chunk.
+const CHUNK_BITS: u32 = 128 * CHUNK_WORDS as u32;
+
+/// One fixed-size slice of a [`ChunkedBitSet`]'s domain.
+///
+/// Ported from rustc_index's `ChunkedBitSet`: an all-zero or all-one chunk costs no allocation,
+/// and only a `Mixed` chunk allocates a dense word array. `Mixed` chunks are reference-counted so
+/// that cloning a [`ChunkedBitSet`] is cheap (as happens on every `ConstrainedDefinitions` clone);
+/// mutation copy-on-writes the backing array via [`Rc::make_mut`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Chunk {
+    /// No bits set in this chunk.
+    Zeros,
+
+    /// Every bit set in this chunk.
+    Ones,
+
+    /// A mix of set and unset bits, stored densely, along with the number of set bits (so that
+    /// [`ChunkedBitSet::len`] doesn't have to popcount every chunk).
+    Mixed(u32, Rc<[u128; CHUNK_WORDS]>),
+}
+
+impl Chunk {
+    fn count(&self) -> u32 {
+        match self {
+            Chunk::Zeros => 0,
+            Chunk::Ones => CHUNK_BITS,
+            Chunk::Mixed(count, _) => *count,
+        }
+    }
+
+    fn word(&self, word_index: usize) -> u128 {
+        match self {
+            Chunk::Zeros => 0,
+            Chunk::Ones => u128::MAX,
+            Chunk::Mixed(_, words) => words[word_index],
+        }
+    }
+}
+
+/// Merge (union) `other` into `me`, returning whether `me` changed.
+fn merge_chunk(me: &mut Chunk, other: &Chunk) -> bool {
+    if matches!(other, Chunk::Zeros) {
+        return false;
+    }
+    if matches!(me, Chunk::Ones) {
+        return false;
+    }
+    if matches!(other, Chunk::Ones) {
+        *me = Chunk::Ones;
+        return true;
+    }
+    let Chunk::Mixed(other_count, other_words) = other else {
+        unreachable!("Zeros and Ones are handled above");
+    };
+    match me {
+        Chunk::Zeros => {
+            *me = Chunk::Mixed(*other_count, Rc::clone(other_words));
+            true
+        }
+        Chunk::Mixed(count, words) => {
+            let words_mut = Rc::make_mut(words);
+            let mut changed = false;
+            for i in 0..CHUNK_WORDS {
+                let merged = words_mut[i] | other_words[i];
+                changed |= merged != words_mut[i];
+                words_mut[i] = merged;
+            }
+            *count = words_mut.iter().map(|word| word.count_ones()).sum();
+            if *count == CHUNK_BITS {
+                *me = Chunk::Ones;
+            }
+            changed
+        }
+        Chunk::Ones => unreachable!("handled above"),
+    }
+}
+
+/// Intersect `me` with `other` in place, returning whether `me` changed.
+fn intersect_chunk(me: &mut Chunk, other: &Chunk) -> bool {
+    if matches!(other, Chunk::Ones) {
+        return false;
+    }
+    if matches!(other, Chunk::Zeros) {
+        let changed = !matches!(me, Chunk::Zeros);
+        *me = Chunk::Zeros;
+        return changed;
+    }
+    if matches!(me, Chunk::Zeros) {
+        return false;
+    }
+    let Chunk::Mixed(other_count, other_words) = other else {
+        unreachable!("Zeros and Ones are handled above");
+    };
+    if matches!(me, Chunk::Ones) {
+        *me = Chunk::Mixed(*other_count, Rc::clone(other_words));
+        return true;
+    }
+    let Chunk::Mixed(count, words) = me else {
+        unreachable!("handled above");
+    };
+    let words_mut = Rc::make_mut(words);
+    let mut changed = false;
+    for i in 0..CHUNK_WORDS {
+        let intersected = words_mut[i] & other_words[i];
+        changed |= intersected != words_mut[i];
+        words_mut[i] = intersected;
+    }
+    *count = words_mut.iter().map(|word| word.count_ones()).sum();
+    if *count == 0 {
+        *me = Chunk::Zeros;
+    }
+    changed
+}
+
+/// Remove every member of `other` from `me`, returning whether `me` changed.
+fn subtract_chunk(me: &mut Chunk, other: &Chunk) -> bool {
+    if matches!(other, Chunk::Zeros) || matches!(me, Chunk::Zeros) {
+        return false;
+    }
+    if matches!(other, Chunk::Ones) {
+        *me = Chunk::Zeros;
+        return true;
+    }
+    let Chunk::Mixed(_, other_words) = other else {
+        unreachable!("Zeros and Ones are handled above");
+    };
+    if matches!(me, Chunk::Ones) {
+        let mut words = [u128::MAX; CHUNK_WORDS];
+        for i in 0..CHUNK_WORDS {
+            words[i] &= !other_words[i];
+        }
+        let count = words.iter().map(|word| word.count_ones()).sum();
+        *me = if count == 0 {
+            Chunk::Zeros
+        } else {
+            Chunk::Mixed(count, Rc::new(words))
+        };
+        return true;
+    }
+    let Chunk::Mixed(count, words) = me else {
+        unreachable!("handled above");
+    };
+    let words_mut = Rc::make_mut(words);
+    let mut changed = false;
+    for i in 0..CHUNK_WORDS {
+        let subtracted = words_mut[i] & !other_words[i];
+        changed |= subtracted != words_mut[i];
+        words_mut[i] = subtracted;
+    }
+    *count = words_mut.iter().map(|word| word.count_ones()).sum();
+    if *count == 0 {
+        *me = Chunk::Zeros;
+    }
+    changed
+}
+
+/// A set of `u32` backed by a `Vec` of fixed-size [`Chunk`]s (each covering [`CHUNK_BITS`]
+/// values), used as the overflow representation for [`super::bitset::BitSet`] once a value no
+/// longer fits in the inline `Blocks` representation.
+///
+/// Chunks past the end of `chunks` are implicitly all-zero, so the domain is effectively
+/// unbounded; `chunks` only grows as large as the highest value ever inserted requires.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub(super) struct ChunkedBitSet {
+    chunks: Vec<Chunk>,
+}
+
+impl ChunkedBitSet {
+    fn chunk_and_bit(value: u32) -> (usize, usize) {
+        ((value / CHUNK_BITS) as usize, (value % CHUNK_BITS) as usize)
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.count() as usize).sum()
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.chunks.iter().all(|chunk| matches!(chunk, Chunk::Zeros))
+    }
+
+    pub(super) fn contains(&self, value: u32) -> bool {
+        let (chunk_index, bit_index) = Self::chunk_and_bit(value);
+        match self.chunks.get(chunk_index) {
+            None => false,
+            Some(chunk) => chunk.word(bit_index / 128) & (1_u128 << (bit_index % 128)) != 0,
+        }
+    }
+
+    pub(super) fn insert(&mut self, value: u32) -> bool {
+        let (chunk_index, bit_index) = Self::chunk_and_bit(value);
+        if chunk_index >= self.chunks.len() {
+            self.chunks.resize(chunk_index + 1, Chunk::Zeros);
+        }
+        let (word_index, bit) = (bit_index / 128, bit_index % 128);
+        let chunk = &mut self.chunks[chunk_index];
+        match chunk {
+            Chunk::Ones => false,
+            Chunk::Zeros => {
+                let mut words = [0_u128; CHUNK_WORDS];
+                words[word_index] = 1_u128 << bit;
+                *chunk = Chunk::Mixed(1, Rc::new(words));
+                true
+            }
+            Chunk::Mixed(count, words) => {
+                let words_mut = Rc::make_mut(words);
+                let missing = words_mut[word_index] & (1_u128 << bit) == 0;
+                if missing {
+                    words_mut[word_index] |= 1_u128 << bit;
+                    *count += 1;
+                    if *count == CHUNK_BITS {
+                        *chunk = Chunk::Ones;
+                    }
+                }
+                missing
+            }
+        }
+    }
+
+    pub(super) fn remove(&mut self, value: u32) -> bool {
+        let (chunk_index, bit_index) = Self::chunk_and_bit(value);
+        let Some(chunk) = self.chunks.get_mut(chunk_index) else {
+            return false;
+        };
+        let (word_index, bit) = (bit_index / 128, bit_index % 128);
+        match chunk {
+            Chunk::Zeros => false,
+            Chunk::Ones => {
+                let mut words = [u128::MAX; CHUNK_WORDS];
+                words[word_index] &= !(1_u128 << bit);
+                *chunk = Chunk::Mixed(CHUNK_BITS - 1, Rc::new(words));
+                true
+            }
+            Chunk::Mixed(count, words) => {
+                let words_mut = Rc::make_mut(words);
+                let present = words_mut[word_index] & (1_u128 << bit) != 0;
+                if present {
+                    words_mut[word_index] &= !(1_u128 << bit);
+                    *count -= 1;
+                    if *count == 0 {
+                        *chunk = Chunk::Zeros;
+                    }
+                }
+                present
+            }
+        }
+    }
+
+    /// Drop trailing all-zero chunks so that an empty set has an empty `chunks` vec.
+    fn shrink(&mut self) {
+        while matches!(self.chunks.last(), Some(Chunk::Zeros)) {
+            self.chunks.pop();
+        }
+    }
+
+    /// The largest value present in this set, or `None` if it's empty.
+    pub(super) fn max(&self) -> Option<u32> {
+        for (chunk_index, chunk) in self.chunks.iter().enumerate().rev() {
+            match chunk {
+                Chunk::Zeros => continue,
+                Chunk::Ones => return Some(chunk_index as u32 * CHUNK_BITS + CHUNK_BITS - 1),
+                Chunk::Mixed(_, words) => {
+                    for (word_index, word) in words.iter().enumerate().rev() {
+                        if *word != 0 {
+                            let bit = 127 - word.leading_zeros();
+                            return Some(
+                                chunk_index as u32 * CHUNK_BITS + word_index as u32 * 128 + bit,
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    pub(super) fn iter(&self) -> ChunkedBitSetIterator<'_> {
+        ChunkedBitSetIterator {
+            chunks: &self.chunks,
+            chunk_index: 0,
+            word_index: 0,
+            cur_word: self.chunks.first().map_or(0, |chunk| chunk.word(0)),
+        }
+    }
+}
+
+impl BitRelations<ChunkedBitSet> for ChunkedBitSet {
+    fn merge(&mut self, other: &ChunkedBitSet) -> bool {
+        if other.chunks.len() > self.chunks.len() {
+            self.chunks.resize(other.chunks.len(), Chunk::Zeros);
+        }
+        let mut changed = false;
+        for (chunk, other_chunk) in self.chunks.iter_mut().zip(&other.chunks) {
+            changed |= merge_chunk(chunk, other_chunk);
+        }
+        changed
+    }
+
+    fn intersect(&mut self, other: &ChunkedBitSet) -> bool {
+        let mut changed = false;
+        for (index, chunk) in self.chunks.iter_mut().enumerate() {
+            let other_chunk = other.chunks.get(index).unwrap_or(&Chunk::Zeros);
+            changed |= intersect_chunk(chunk, other_chunk);
+        }
+        self.shrink();
+        changed
+    }
+
+    fn subtract(&mut self, other: &ChunkedBitSet) -> bool {
+        let mut changed = false;
+        for (chunk, other_chunk) in self.chunks.iter_mut().zip(&other.chunks) {
+            changed |= subtract_chunk(chunk, other_chunk);
+        }
+        self.shrink();
+        changed
+    }
+}
+
+/// Iterator over the values (in ascending order) of a [`ChunkedBitSet`].
+#[derive(Debug)]
+pub(super) struct ChunkedBitSetIterator<'a> {
+    chunks: &'a [Chunk],
+    chunk_index: usize,
+    word_index: usize,
+    cur_word: u128,
+}
+
+impl Iterator for ChunkedBitSetIterator<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.cur_word == 0 {
+            self.word_index += 1;
+            if self.word_index == CHUNK_WORDS {
+                self.word_index = 0;
+                self.chunk_index += 1;
+            }
+            if self.chunk_index >= self.chunks.len() {
+                return None;
+            }
+            self.cur_word = self.chunks[self.chunk_index].word(self.word_index);
+        }
+        let bit = self.cur_word.trailing_zeros();
+        self.cur_word &= self.cur_word.wrapping_sub(1);
+        Some(bit + 128 * self.word_index as u32 + CHUNK_BITS * self.chunk_index as u32)
+    }
+}
+
+impl std::iter::FusedIterator for ChunkedBitSetIterator<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitRelations, ChunkedBitSet, CHUNK_BITS};
+
+    fn assert_set(set: &ChunkedBitSet, contents: &[u32]) {
+        assert_eq!(set.iter().collect::<Vec<_>>(), contents);
+    }
+
+    #[test]
+    fn insert_and_iter() {
+        let mut set = ChunkedBitSet::default();
+        assert!(set.insert(5));
+        assert!(!set.insert(5));
+        assert!(set.insert(CHUNK_BITS + 3));
+        assert_set(&set, &[5, CHUNK_BITS + 3]);
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn remove_demotes_ones_and_drains_to_zeros() {
+        let mut set = ChunkedBitSet::default();
+        for value in 0..CHUNK_BITS {
+            set.insert(value);
+        }
+        assert!(set.remove(17));
+        assert_eq!(set.len() as u32, CHUNK_BITS - 1);
+        assert!(!set.contains(17));
+
+        let mut empty = ChunkedBitSet::default();
+        empty.insert(9);
+        assert!(empty.remove(9));
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn merge_intersect_subtract() {
+        let mut a = ChunkedBitSet::default();
+        a.insert(1);
+        a.insert(CHUNK_BITS + 1);
+
+        let mut b = ChunkedBitSet::default();
+        b.insert(2);
+        b.insert(CHUNK_BITS + 1);
+
+        let mut merged = a.clone();
+        assert!(merged.merge(&b));
+        assert_set(&merged, &[1, 2, CHUNK_BITS + 1]);
+
+        let mut intersected = a.clone();
+        assert!(intersected.intersect(&b));
+        assert_set(&intersected, &[CHUNK_BITS + 1]);
+
+        let mut subtracted = a.clone();
+        assert!(subtracted.subtract(&b));
+        assert_set(&subtracted, &[1]);
+        assert!(!subtracted.subtract(&b));
+    }
+}
@@ -1,4 +1,5 @@
-use super::bitset::{BitSet, BitSetArray, BitSetArrayIterator, BitSetIterator};
+use super::bitset::{BitRelations, BitSet, BitSetArray, BitSetArrayIterator, BitSetIterator, Idx};
+use super::interval::IntervalSet;
 use ruff_index::newtype_index;
 
 #[newtype_index]
@@ -7,11 +8,31 @@ pub(super) struct ScopedDefinitionId;
 #[newtype_index]
 pub(super) struct ScopedConstraintId;
 
+impl Idx for ScopedDefinitionId {
+    fn index(&self) -> usize {
+        self.as_u32() as usize
+    }
+
+    fn from_usize(value: usize) -> Self {
+        ScopedDefinitionId::from_u32(value as u32)
+    }
+}
+
+impl Idx for ScopedConstraintId {
+    fn index(&self) -> usize {
+        self.as_u32() as usize
+    }
+
+    fn from_usize(value: usize) -> Self {
+        ScopedConstraintId::from_u32(value as u32)
+    }
+}
+
 /// Can reference this * 128 definitions efficiently; tune for performance vs memory.
 const DEFINITION_BLOCKS: usize = 4;
 
-type Definitions = BitSet<DEFINITION_BLOCKS>;
-type DefinitionsIterator<'a> = BitSetIterator<'a, DEFINITION_BLOCKS>;
+type Definitions = BitSet<ScopedDefinitionId, DEFINITION_BLOCKS>;
+type DefinitionsIterator<'a> = BitSetIterator<'a, ScopedDefinitionId, DEFINITION_BLOCKS>;
 
 /// Can reference this * 128 constraints efficiently; tune for performance vs memory.
 const CONSTRAINT_BLOCKS: usize = 4;
@@ -19,9 +40,14 @@ const CONSTRAINT_BLOCKS: usize = 4;
 /// Can handle this many visible definitions per symbol at a given time efficiently.
 const MAX_EXPECTED_VISIBLE_DEFINITIONS_PER_SYMBOL: usize = 16;
 
-type Constraints = BitSetArray<CONSTRAINT_BLOCKS, MAX_EXPECTED_VISIBLE_DEFINITIONS_PER_SYMBOL>;
-type ConstraintsIterator<'a> =
-    BitSetArrayIterator<'a, CONSTRAINT_BLOCKS, MAX_EXPECTED_VISIBLE_DEFINITIONS_PER_SYMBOL>;
+type Constraints =
+    BitSetArray<ScopedConstraintId, CONSTRAINT_BLOCKS, MAX_EXPECTED_VISIBLE_DEFINITIONS_PER_SYMBOL>;
+type ConstraintsIterator<'a> = BitSetArrayIterator<
+    'a,
+    ScopedConstraintId,
+    CONSTRAINT_BLOCKS,
+    MAX_EXPECTED_VISIBLE_DEFINITIONS_PER_SYMBOL,
+>;
 
 /// Constrained definitions visible for a symbol at a particular point.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -57,7 +83,7 @@ impl ConstrainedDefinitions {
 
     pub(super) fn with(definition_id: ScopedDefinitionId) -> Self {
         Self {
-            visible_definitions: Definitions::with(definition_id.into()),
+            visible_definitions: Definitions::with(definition_id),
             constraints: Constraints::of_size(1),
             may_be_unbound: false,
         }
@@ -70,7 +96,23 @@ impl ConstrainedDefinitions {
 
     /// Add given constraint index to all definitions
     pub(super) fn add_constraint(&mut self, constraint_id: ScopedConstraintId) {
-        self.constraints.insert_in_each(constraint_id.into());
+        self.constraints.insert_in_each(constraint_id);
+    }
+
+    /// Add every constraint in the inclusive range `[start, end]` to all definitions.
+    ///
+    /// Builds the range as an [`IntervalSet`] (a single O(log n) insert) and converts it to a
+    /// [`BitSet`] once, instead of calling [`Self::add_constraint`] once per constraint in the
+    /// range.
+    pub(super) fn add_constraint_range(
+        &mut self,
+        start: ScopedConstraintId,
+        end: ScopedConstraintId,
+    ) {
+        let mut range = IntervalSet::new();
+        range.insert_range(start, end);
+        let constraints: BitSet<ScopedConstraintId, CONSTRAINT_BLOCKS> = (&range).into();
+        self.constraints.merge_in_each(&constraints);
     }
 
     /// Merge two [`ConstrainedDefinitions`].
@@ -88,8 +130,8 @@ impl ConstrainedDefinitions {
         let mut a_constraints_iter = a.constraints.iter();
         let mut b_constraints_iter = b.constraints.iter();
 
-        let mut opt_a_def: Option<u32> = a_defs_iter.next();
-        let mut opt_b_def: Option<u32> = b_defs_iter.next();
+        let mut opt_a_def: Option<ScopedDefinitionId> = a_defs_iter.next();
+        let mut opt_b_def: Option<ScopedDefinitionId> = b_defs_iter.next();
 
         // Iterate through the definitions from `a` and `b` in sync (always processing the lower
         // definition ID first), and pushing each definition onto the merged
@@ -154,6 +196,18 @@ impl ConstrainedDefinitions {
     pub(super) fn may_be_unbound(&self) -> bool {
         self.may_be_unbound
     }
+
+    /// If there is exactly one possible definition, and the symbol cannot be unbound, return it
+    /// (with its constraints) directly.
+    ///
+    /// This is the overwhelmingly common case (an unambiguous binding), so it's worth letting
+    /// callers skip the full [`Self::iter_visible_definitions`] machinery for it.
+    pub(super) fn single_definition(&self) -> Option<DefinitionIdWithConstraints> {
+        if self.may_be_unbound || self.visible_definitions.len() != 1 {
+            return None;
+        }
+        self.iter_visible_definitions().next()
+    }
 }
 
 impl Default for ConstrainedDefinitions {
@@ -174,8 +228,8 @@ impl<'a> Iterator for DefinitionIdWithConstraintsIterator<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         match (self.definitions.next(), self.constraints.next()) {
             (None, None) => None,
-            (Some(def), Some(constraints)) => Some(DefinitionIdWithConstraints {
-                definition: ScopedDefinitionId::from_u32(def),
+            (Some(definition), Some(constraints)) => Some(DefinitionIdWithConstraints {
+                definition,
                 constraint_ids: ConstraintIdIterator {
                     wrapped: constraints.iter(),
                 },
@@ -189,14 +243,14 @@ impl std::iter::FusedIterator for DefinitionIdWithConstraintsIterator<'_> {}
 
 #[derive(Debug)]
 pub(super) struct ConstraintIdIterator<'a> {
-    wrapped: BitSetIterator<'a, CONSTRAINT_BLOCKS>,
+    wrapped: BitSetIterator<'a, ScopedConstraintId, CONSTRAINT_BLOCKS>,
 }
 
 impl Iterator for ConstraintIdIterator<'_> {
     type Item = ScopedConstraintId;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.wrapped.next().map(ScopedConstraintId::from_u32)
+        self.wrapped.next()
     }
 }
 
@@ -259,6 +313,38 @@ mod tests {
         assert_eq!(cd.defs(), &["0<0>"]);
     }
 
+    #[test]
+    fn add_constraint_range() {
+        let mut cd = ConstrainedDefinitions::with(ScopedDefinitionId::from_u32(0));
+        cd.add_constraint_range(
+            ScopedConstraintId::from_u32(2),
+            ScopedConstraintId::from_u32(4),
+        );
+
+        assert!(!cd.may_be_unbound());
+        assert_eq!(cd.defs(), &["0<2, 3, 4>"]);
+    }
+
+    #[test]
+    fn single_definition() {
+        let cd = ConstrainedDefinitions::with(ScopedDefinitionId::from_u32(0));
+        let single = cd.single_definition().expect("single definition");
+        assert_eq!(single.definition, ScopedDefinitionId::from_u32(0));
+
+        let mut unbound_possible = cd.clone();
+        unbound_possible.add_unbound();
+        assert!(unbound_possible.single_definition().is_none());
+
+        let merged = ConstrainedDefinitions::merge(&cd, &ConstrainedDefinitions::unbound());
+        assert!(merged.single_definition().is_none());
+
+        let ambiguous = ConstrainedDefinitions::merge(
+            &cd,
+            &ConstrainedDefinitions::with(ScopedDefinitionId::from_u32(1)),
+        );
+        assert!(ambiguous.single_definition().is_none());
+    }
+
     #[test]
     fn merge() {
         // merging the same definition with the same constraint keeps the constraint
@@ -0,0 +1,237 @@
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+
+use super::bitset::{BitSet, Idx};
+
+/// A sorted, coalescing set of inclusive `[start, end]` ranges of `I`.
+///
+/// Modeled on rustc_index's `IntervalSet`: good for values that tend to arrive in contiguous
+/// runs (e.g. "every constraint that applies to the definitions visible at this point"), where a
+/// single insert can cover many values in O(log n) instead of one bit at a time. Falls back
+/// poorly to sparse, scattered values; use [`BitSet`] for those instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct IntervalSet<I> {
+    /// Sorted, non-adjacent, non-overlapping `[start, end]` ranges.
+    ranges: Vec<(u32, u32)>,
+
+    _idx: PhantomData<I>,
+}
+
+impl<I> Default for IntervalSet<I> {
+    fn default() -> Self {
+        Self {
+            ranges: Vec::new(),
+            _idx: PhantomData,
+        }
+    }
+}
+
+impl<I: Idx> IntervalSet<I> {
+    pub(super) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub(super) fn contains(&self, value: I) -> bool {
+        let value = value.index() as u32;
+        self.ranges
+            .binary_search_by(|&(start, end)| {
+                if value < start {
+                    Ordering::Greater
+                } else if value > end {
+                    Ordering::Less
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+
+    /// Insert a single value. Return true if the value was newly inserted.
+    pub(super) fn insert(&mut self, value: I) -> bool {
+        self.insert_range(value, value)
+    }
+
+    /// Insert the inclusive range `[start, end]`, coalescing with any adjacent or overlapping
+    /// ranges. Return true if this added any value not already present.
+    pub(super) fn insert_range(&mut self, start: I, end: I) -> bool {
+        let mut start = start.index() as u32;
+        let mut end = end.index() as u32;
+        debug_assert!(start <= end);
+
+        // The first existing range that could overlap or be adjacent to `[start, end]`.
+        let insertion_point = self
+            .ranges
+            .partition_point(|&(_, existing_end)| existing_end + 1 < start);
+
+        if let Some(&(existing_start, existing_end)) = self.ranges.get(insertion_point) {
+            if existing_start <= start && end <= existing_end {
+                return false;
+            }
+        }
+
+        // Absorb every following range that overlaps or touches `[start, end]`.
+        let mut remove_to = insertion_point;
+        while remove_to < self.ranges.len() && self.ranges[remove_to].0 <= end + 1 {
+            let (existing_start, existing_end) = self.ranges[remove_to];
+            start = start.min(existing_start);
+            end = end.max(existing_end);
+            remove_to += 1;
+        }
+
+        self.ranges.splice(insertion_point..remove_to, [(start, end)]);
+        true
+    }
+
+    /// Return an iterator over the values (in ascending order) in this set.
+    pub(super) fn iter(&self) -> IntervalSetIterator<'_, I> {
+        IntervalSetIterator {
+            ranges: &self.ranges,
+            range_index: 0,
+            next_value: self.ranges.first().map(|&(start, _)| start),
+            _idx: PhantomData,
+        }
+    }
+
+    /// Intersect in place with `other`, in a single pass over both sorted range lists.
+    /// Return true if `self` changed.
+    pub(super) fn intersect(&mut self, other: &IntervalSet<I>) -> bool {
+        let mut result = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a_start, a_end) = self.ranges[i];
+            let (b_start, b_end) = other.ranges[j];
+            let start = a_start.max(b_start);
+            let end = a_end.min(b_end);
+            if start <= end {
+                result.push((start, end));
+            }
+            if a_end < b_end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        let changed = result != self.ranges;
+        self.ranges = result;
+        changed
+    }
+}
+
+/// Build an [`IntervalSet`] from every member of a [`BitSet`].
+impl<I: Idx, const B: usize> From<&BitSet<I, B>> for IntervalSet<I> {
+    fn from(bitset: &BitSet<I, B>) -> Self {
+        let mut set = IntervalSet::new();
+        for value in bitset.iter() {
+            set.insert(value);
+        }
+        set
+    }
+}
+
+/// Convert an [`IntervalSet`] back to a [`BitSet`] holding every member.
+impl<I: Idx, const B: usize> From<&IntervalSet<I>> for BitSet<I, B> {
+    fn from(intervals: &IntervalSet<I>) -> Self {
+        let mut set = BitSet::default();
+        for value in intervals.iter() {
+            set.insert(value);
+        }
+        set
+    }
+}
+
+/// Iterator over values in an [`IntervalSet`].
+#[derive(Debug)]
+pub(super) struct IntervalSetIterator<'a, I> {
+    ranges: &'a [(u32, u32)],
+    range_index: usize,
+    next_value: Option<u32>,
+    _idx: PhantomData<I>,
+}
+
+impl<I: Idx> Iterator for IntervalSetIterator<'_, I> {
+    type Item = I;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.next_value?;
+        let (_, end) = self.ranges[self.range_index];
+        self.next_value = if value < end {
+            Some(value + 1)
+        } else {
+            self.range_index += 1;
+            self.ranges.get(self.range_index).map(|&(start, _)| start)
+        };
+        Some(I::from_usize(value as usize))
+    }
+}
+
+impl<I: Idx> std::iter::FusedIterator for IntervalSetIterator<'_, I> {}
+
+#[cfg(test)]
+mod tests {
+    use super::IntervalSet;
+
+    fn assert_set(set: &IntervalSet<u32>, contents: &[u32]) {
+        assert_eq!(set.iter().collect::<Vec<_>>(), contents);
+    }
+
+    #[test]
+    fn insert_and_contains() {
+        let mut set = IntervalSet::<u32>::new();
+        assert!(set.is_empty());
+
+        assert!(set.insert(4));
+        assert!(set.contains(4));
+        assert!(!set.contains(5));
+        assert!(!set.insert(4));
+    }
+
+    #[test]
+    fn insert_range_coalesces_adjacent_and_overlapping() {
+        let mut set = IntervalSet::<u32>::new();
+        set.insert_range(4, 6);
+        set.insert_range(10, 12);
+        assert_set(&set, &[4, 5, 6, 10, 11, 12]);
+
+        // adjacent to both ranges at once: coalesces everything into [4, 12]
+        set.insert_range(7, 9);
+        assert_set(&set, &[4, 5, 6, 7, 8, 9, 10, 11, 12]);
+
+        // overlaps the remaining run on both sides: widens it to [0, 20]
+        set.insert_range(0, 20);
+        assert_set(&set, &(0..=20).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn intersect() {
+        let mut a = IntervalSet::<u32>::new();
+        a.insert_range(0, 10);
+
+        let mut b = IntervalSet::<u32>::new();
+        b.insert_range(5, 15);
+        b.insert_range(20, 25);
+
+        assert!(a.intersect(&b));
+        assert_set(&a, &[5, 6, 7, 8, 9, 10]);
+
+        assert!(!a.intersect(&b));
+    }
+
+    #[test]
+    fn roundtrip_through_bitset() {
+        use super::super::bitset::BitSet;
+
+        let mut set = IntervalSet::<u32>::new();
+        set.insert_range(4, 6);
+        set.insert(140);
+
+        let bitset: BitSet<u32, 1> = (&set).into();
+        assert_eq!(bitset.iter().collect::<Vec<_>>(), &[4, 5, 6, 140]);
+
+        let roundtripped: IntervalSet<u32> = (&bitset).into();
+        assert_set(&roundtripped, &[4, 5, 6, 140]);
+    }
+}
@@ -3,21 +3,54 @@ use crate::semantic_index::definition::Definition;
 use crate::semantic_index::expression::Expression;
 use crate::semantic_index::symbol::{ScopeId, ScopedSymbolId, SymbolTable};
 use crate::semantic_index::symbol_table;
-use crate::types::{infer_expression_types, IntersectionTypeBuilder, Type, TypeInference};
+use crate::types::{
+    infer_expression_types, IntersectionTypeBuilder, Type, TypeInference, UnionTypeBuilder,
+};
 use crate::Db;
 use ruff_python_ast as ast;
 use rustc_hash::FxHashMap;
 use std::sync::Arc;
 
-/// Return type constraint, if any, on `definition` applied by `test`.
+/// Which edge of a test's control flow we want the narrowed type for: the edge taken when the
+/// test is truthy (an `if` body, or the left operand of a short-circuited `and`/`or`), or the edge
+/// taken when it's falsy (an `else` body, or a negation).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConstraintPolarity {
+    Positive,
+    Negative,
+}
+
+/// Return type constraint, if any, on `definition` applied by `test`, for the control-flow edge
+/// given by `polarity`.
 pub(crate) fn narrowing_constraint<'db>(
     db: &'db dyn Db,
     test: Expression<'db>,
     definition: Definition<'db>,
+    polarity: ConstraintPolarity,
 ) -> Option<Type<'db>> {
-    all_narrowing_constraints(db, test)
-        .get(&definition.symbol(db))
-        .copied()
+    let constraints = all_narrowing_constraints(db, test);
+    let map = match polarity {
+        ConstraintPolarity::Positive => &constraints.positive,
+        ConstraintPolarity::Negative => &constraints.negative,
+    };
+    map.get(&definition.symbol(db)).copied()
+}
+
+/// Both edges' constraints on `definition` applied by `test` at once: `(positive, negative)`.
+///
+/// This is the shape the caller applying `test`'s narrowing to an `if`/`else` pair actually wants
+/// -- one type for the body taken when `test` is truthy, one for the body taken when it's falsy --
+/// so it's the one that should be used in place of calling [`narrowing_constraint`] twice by hand
+/// (which risks the two calls drifting out of sync on which edge is which).
+pub(crate) fn narrowing_constraints<'db>(
+    db: &'db dyn Db,
+    test: Expression<'db>,
+    definition: Definition<'db>,
+) -> (Option<Type<'db>>, Option<Type<'db>>) {
+    (
+        narrowing_constraint(db, test, definition, ConstraintPolarity::Positive),
+        narrowing_constraint(db, test, definition, ConstraintPolarity::Negative),
+    )
 }
 
 #[salsa::tracked]
@@ -28,31 +61,42 @@ fn all_narrowing_constraints<'db>(
     NarrowingConstraintsBuilder::new(db, test).finish()
 }
 
-type NarrowingConstraints<'db> = FxHashMap<ScopedSymbolId, Type<'db>>;
+type ConstraintMap<'db> = FxHashMap<ScopedSymbolId, Type<'db>>;
+
+/// Per-symbol type constraints implied by a test expression, one map for the edge taken when the
+/// test is true and one for the edge taken when it's false.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+struct NarrowingConstraints<'db> {
+    positive: ConstraintMap<'db>,
+    negative: ConstraintMap<'db>,
+}
+
+impl<'db> NarrowingConstraints<'db> {
+    /// A constraint on a single symbol, narrowing it to `positive_ty` on the true edge and
+    /// `negative_ty` on the false edge.
+    fn single(symbol: ScopedSymbolId, positive_ty: Type<'db>, negative_ty: Type<'db>) -> Self {
+        let mut result = Self::default();
+        result.positive.insert(symbol, positive_ty);
+        result.negative.insert(symbol, negative_ty);
+        result
+    }
+}
 
 struct NarrowingConstraintsBuilder<'db> {
     db: &'db dyn Db,
     expression: Expression<'db>,
-    constraints: NarrowingConstraints<'db>,
 }
 
 impl<'db> NarrowingConstraintsBuilder<'db> {
     fn new(db: &'db dyn Db, expression: Expression<'db>) -> Self {
-        Self {
-            db,
-            expression,
-            constraints: NarrowingConstraints::default(),
-        }
+        Self { db, expression }
     }
 
-    fn finish(mut self) -> NarrowingConstraints<'db> {
-        if let ast::Expr::Compare(expr_compare) = self.expression.node(self.db).node() {
-            self.add_expr_compare(expr_compare);
-        }
-        // TODO other test expression kinds
-
-        self.constraints.shrink_to_fit();
-        self.constraints
+    fn finish(self) -> NarrowingConstraints<'db> {
+        let mut constraints = self.evaluate_expr(self.expression.node(self.db).node());
+        constraints.positive.shrink_to_fit();
+        constraints.negative.shrink_to_fit();
+        constraints
     }
 
     fn symbols(&self) -> Arc<SymbolTable> {
@@ -67,7 +111,55 @@ impl<'db> NarrowingConstraintsBuilder<'db> {
         infer_expression_types(self.db, self.expression)
     }
 
-    fn add_expr_compare(&mut self, expr_compare: &ast::ExprCompare) {
+    fn expr_ty(&self, expr: &ast::Expr) -> Type<'db> {
+        self.inference()
+            .expression_ty(expr.scoped_ast_id(self.db, self.scope()))
+    }
+
+    /// Resolve `expr` to the [`ScopedSymbolId`] it names, if it's a bare name. Only bare-name
+    /// operands are narrowable; attribute and subscript targets aren't tracked by the use-def map.
+    fn as_symbol(&self, expr: &ast::Expr) -> Option<ScopedSymbolId> {
+        let ast::Expr::Name(ast::ExprName { id, .. }) = expr else {
+            return None;
+        };
+        // SAFETY: we should always have a symbol for every Name node.
+        Some(self.symbols().symbol_id_by_name(id).unwrap())
+    }
+
+    fn evaluate_expr(&self, expr: &ast::Expr) -> NarrowingConstraints<'db> {
+        match expr {
+            ast::Expr::Compare(expr_compare) => self.evaluate_expr_compare(expr_compare),
+            ast::Expr::Call(expr_call) => self.evaluate_expr_call(expr_call),
+            ast::Expr::BoolOp(expr_bool_op) => self.evaluate_expr_bool_op(expr_bool_op),
+            ast::Expr::UnaryOp(expr_unary_op) if expr_unary_op.op == ast::UnaryOp::Not => {
+                self.negate(self.evaluate_expr(&expr_unary_op.operand))
+            }
+            // TODO other test expression kinds
+            _ => NarrowingConstraints::default(),
+        }
+    }
+
+    fn evaluate_expr_bool_op(&self, expr_bool_op: &ast::ExprBoolOp) -> NarrowingConstraints<'db> {
+        let ast::ExprBoolOp {
+            range: _,
+            op,
+            values,
+        } = expr_bool_op;
+
+        let mut operands = values.iter().map(|value| self.evaluate_expr(value));
+        let Some(mut result) = operands.next() else {
+            return NarrowingConstraints::default();
+        };
+        for operand in operands {
+            result = match op {
+                ast::BoolOp::And => self.both(result, operand),
+                ast::BoolOp::Or => self.either(result, operand),
+            };
+        }
+        result
+    }
+
+    fn evaluate_expr_compare(&self, expr_compare: &ast::ExprCompare) -> NarrowingConstraints<'db> {
         let ast::ExprCompare {
             range: _,
             left,
@@ -75,26 +167,161 @@ impl<'db> NarrowingConstraintsBuilder<'db> {
             comparators,
         } = expr_compare;
 
-        if let ast::Expr::Name(ast::ExprName {
+        // A chained comparison like `a is b is not c` means `(a is b) and (b is not c)`: each
+        // clause's left-hand operand is the previous clause's comparator, not `left` again, so we
+        // thread it through the loop. A clause whose left-hand operand isn't a bare name (either
+        // `left` itself, or an intermediate comparator) contributes nothing, but doesn't abort the
+        // rest of the chain -- e.g. `f() is x is not None` still narrows `x` in its second clause
+        // even though the first clause's left-hand operand isn't narrowable.
+        let mut current_left = *left.clone();
+        let mut result = NarrowingConstraints::default();
+        let mut have_clause = false;
+        for (op, comparator) in std::iter::zip(ops, comparators) {
+            if let Some(symbol) = self.as_symbol(&current_left) {
+                let comp_ty = self.expr_ty(comparator);
+                if let Some(clause) = self.single_comparison_constraint(symbol, *op, comp_ty) {
+                    result = if have_clause {
+                        self.both(result, clause)
+                    } else {
+                        clause
+                    };
+                    have_clause = true;
+                }
+            }
+            current_left = comparator.clone();
+        }
+        result
+    }
+
+    fn single_comparison_constraint(
+        &self,
+        symbol: ScopedSymbolId,
+        op: ast::CmpOp,
+        comp_ty: Type<'db>,
+    ) -> Option<NarrowingConstraints<'db>> {
+        let negated_ty = IntersectionTypeBuilder::new(self.db)
+            .add_negative(comp_ty)
+            .build();
+        match op {
+            ast::CmpOp::Is | ast::CmpOp::Eq => {
+                Some(NarrowingConstraints::single(symbol, comp_ty, negated_ty))
+            }
+            ast::CmpOp::IsNot | ast::CmpOp::NotEq => {
+                Some(NarrowingConstraints::single(symbol, negated_ty, comp_ty))
+            }
+            // TODO ordering comparisons (`<`, `<=`, `>`, `>=`) don't narrow a type today
+            _ => None,
+        }
+    }
+
+    fn evaluate_expr_call(&self, expr_call: &ast::ExprCall) -> NarrowingConstraints<'db> {
+        let Some((symbol, class_ty)) = self.isinstance_call_operands(expr_call) else {
+            return NarrowingConstraints::default();
+        };
+        let negated_ty = IntersectionTypeBuilder::new(self.db)
+            .add_negative(class_ty)
+            .build();
+        NarrowingConstraints::single(symbol, class_ty, negated_ty)
+    }
+
+    /// If `expr_call` is a bare `isinstance(x, T)` or `issubclass(x, T)` call, return the
+    /// narrowed symbol and `T`'s type.
+    ///
+    /// We match on the function name alone, the same way [`Self::as_symbol`] matches operands
+    /// without resolving imports; a local rebound to a different callable under the name
+    /// `isinstance` or `issubclass` would produce a (harmless) false positive here.
+    fn isinstance_call_operands(
+        &self,
+        expr_call: &ast::ExprCall,
+    ) -> Option<(ScopedSymbolId, Type<'db>)> {
+        let ast::Expr::Name(ast::ExprName {
+            id: func_name,
             range: _,
-            id,
             ctx: _,
-        }) = left.as_ref()
-        {
-            // SAFETY: we should always have a symbol for every Name node.
-            let symbol = self.symbols().symbol_id_by_name(id).unwrap();
-            for (op, comparator) in std::iter::zip(ops, comparators) {
-                let comp_ty = self
-                    .inference()
-                    .expression_ty(comparator.scoped_ast_id(self.db, self.scope()));
-                if matches!(op, ast::CmpOp::IsNot) {
-                    let ty = IntersectionTypeBuilder::new(self.db)
-                        .add_negative(comp_ty)
+        }) = expr_call.func.as_ref()
+        else {
+            return None;
+        };
+        if func_name != "isinstance" && func_name != "issubclass" {
+            return None;
+        }
+        let [obj, class_arg] = expr_call.arguments.args.as_ref() else {
+            return None;
+        };
+        let symbol = self.as_symbol(obj)?;
+        Some((symbol, self.expr_ty(class_arg)))
+    }
+
+    /// Swap the edges of `constraints`: whatever held on the true edge now holds on the false
+    /// edge, and vice versa. Implements `not`.
+    fn negate(&self, constraints: NarrowingConstraints<'db>) -> NarrowingConstraints<'db> {
+        NarrowingConstraints {
+            positive: constraints.negative,
+            negative: constraints.positive,
+        }
+    }
+
+    /// Combine `a` and `b` into the constraints that hold when *both* hold (`and`'s true edge,
+    /// `or`'s false edge via De Morgan's law).
+    fn both(
+        &self,
+        a: NarrowingConstraints<'db>,
+        b: NarrowingConstraints<'db>,
+    ) -> NarrowingConstraints<'db> {
+        NarrowingConstraints {
+            positive: self.merge_conjunctive(a.positive, b.positive),
+            negative: self.merge_disjunctive(a.negative, b.negative),
+        }
+    }
+
+    /// Combine `a` and `b` into the constraints that hold when *at least one* holds (`or`'s true
+    /// edge, `and`'s false edge via De Morgan's law).
+    fn either(
+        &self,
+        a: NarrowingConstraints<'db>,
+        b: NarrowingConstraints<'db>,
+    ) -> NarrowingConstraints<'db> {
+        NarrowingConstraints {
+            positive: self.merge_disjunctive(a.positive, b.positive),
+            negative: self.merge_conjunctive(a.negative, b.negative),
+        }
+    }
+
+    /// Merge two constraint maps under "both must hold": a symbol constrained by only one side
+    /// keeps that side's type unchanged (the other conjunct/disjunct simply says nothing about
+    /// it); a symbol constrained by both is narrowed to the intersection of the two types.
+    fn merge_conjunctive(
+        &self,
+        mut a: ConstraintMap<'db>,
+        b: ConstraintMap<'db>,
+    ) -> ConstraintMap<'db> {
+        for (symbol, b_ty) in b {
+            a.entry(symbol)
+                .and_modify(|a_ty| {
+                    *a_ty = IntersectionTypeBuilder::new(self.db)
+                        .add_positive(*a_ty)
+                        .add_positive(b_ty)
                         .build();
-                    self.constraints.insert(symbol, ty);
-                };
-                // TODO other comparison types
-            }
+                })
+                .or_insert(b_ty);
         }
+        a
+    }
+
+    /// Merge two constraint maps under "at least one must hold": a symbol constrained by only one
+    /// side carries no information (we don't know which side actually held), so it's dropped; a
+    /// symbol constrained by both is widened to the union of the two types.
+    fn merge_disjunctive(
+        &self,
+        a: ConstraintMap<'db>,
+        b: ConstraintMap<'db>,
+    ) -> ConstraintMap<'db> {
+        a.into_iter()
+            .filter_map(|(symbol, a_ty)| {
+                let b_ty = *b.get(&symbol)?;
+                let ty = UnionTypeBuilder::new(self.db).add(a_ty).add(b_ty).build();
+                Some((symbol, ty))
+            })
+            .collect()
     }
 }